@@ -1,3 +1,4 @@
+pub mod camera;
 pub mod color;
 pub mod default;
 pub mod file;
@@ -7,4 +8,5 @@ pub mod project;
 pub mod resource;
 pub mod seed;
 pub mod shader;
+pub mod viewport;
 pub mod window;
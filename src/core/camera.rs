@@ -0,0 +1,394 @@
+use std::ops::Deref;
+use crate::core::math::collision::rotate_point;
+use crate::core::math::culling::Bounds;
+use crate::core::math::transform::Transform;
+
+/// a camera for 2d games, its position is the point of the world
+/// that is shown in the center of the viewport
+///
+/// it can be used everywhere a camera Transform is used
+/// ```rust
+/// let mut camera = Camera2D::new(800.0, 600.0);
+/// camera.set_bounds(Some((0.0, 0.0, 4000.0, 3000.0)));
+/// camera.set_dead_zone(64.0, 32.0);
+///
+/// // every frame
+/// let delta = draw.performance.delta();
+/// camera.follow(player_x, player_y, delta);
+/// camera.update(delta);
+/// let (world_x, world_y) = camera.screen_to_world(draw.mouse.x, draw.mouse.y);
+/// player.draw(&draw, &camera)?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Camera2D {
+    position: (f32, f32),
+    zoom: f32,
+    rotation: f32,
+    // x, y, width and height on the screen
+    viewport: Bounds,
+    // the area the view has to stay in
+    bounds: Option<Bounds>,
+    // width and height of the area around the position
+    // a followed target can move in without moving the camera
+    dead_zone: (f32, f32),
+    // how fast the camera catches up with its target, 0.0 snaps
+    smoothing: f32,
+    trauma: f32,
+    // how much trauma is lost every second
+    trauma_decay: f32,
+    // the offset and rotation at full trauma
+    max_shake: (f32, f32),
+    time: f32,
+    // x, y and rotation the shake adds right now
+    shake: (f32, f32, f32),
+    transform: Transform,
+}
+
+impl Camera2D {
+    // create a camera with a viewport that covers a screen of width and height
+    pub fn new(width: f32, height: f32) -> Self {
+        let mut camera = Self {
+            position: (width / 2.0, height / 2.0),
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport: (0.0, 0.0, width, height),
+            bounds: None,
+            dead_zone: (0.0, 0.0),
+            smoothing: 0.0,
+            trauma: 0.0,
+            trauma_decay: 1.0,
+            max_shake: (16.0, 0.05),
+            time: 0.0,
+            shake: (0.0, 0.0, 0.0),
+            transform: Transform::new(),
+        };
+        camera.refresh();
+        camera
+    }
+
+    // set the point of the world that is shown in the center
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = (x, y);
+        self.refresh();
+    }
+
+    // the point of the world that is shown in the center
+    pub fn position(&self) -> (f32, f32) {
+        self.position
+    }
+
+    // move the camera in the world
+    pub fn move_by(&mut self, x: f32, y: f32) {
+        self.set_position(self.position.0 + x, self.position.1 + y);
+    }
+
+    // set the zoom, 2.0 shows everything twice as big
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(0.001);
+        self.refresh();
+    }
+
+    // the zoom
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    // set the zoom and keep the world point
+    // under a point on the screen in place, e.g. the mouse
+    pub fn zoom_at(&mut self, screen_x: f32, screen_y: f32, zoom: f32) {
+        let (world_x, world_y) = self.screen_to_world(screen_x, screen_y);
+        self.zoom = zoom.max(0.001);
+        self.refresh();
+
+        let (moved_x, moved_y) = self.screen_to_world(screen_x, screen_y);
+        self.move_by(world_x - moved_x, world_y - moved_y);
+    }
+
+    // set the rotation in radians
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.refresh();
+    }
+
+    // the rotation in radians
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    // set the part of the screen the camera draws to
+    pub fn set_viewport(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.viewport = (x, y, width, height);
+        self.refresh();
+    }
+
+    // the part of the screen the camera draws to
+    pub fn viewport(&self) -> Bounds {
+        self.viewport
+    }
+
+    // set the area of the world the view has to stay in,
+    // an area smaller than the view keeps it centered
+    pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+        self.bounds = bounds;
+        self.refresh();
+    }
+
+    // the area of the world the view has to stay in
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    // set the size of the area a followed target can move in
+    pub fn set_dead_zone(&mut self, width: f32, height: f32) {
+        self.dead_zone = (width.max(0.0), height.max(0.0));
+    }
+
+    // set how fast follow catches up, 0.0 moves to the target at once
+    pub fn set_smoothing(&mut self, smoothing: f32) {
+        self.smoothing = smoothing.max(0.0);
+    }
+
+    // move towards a target once it leaves the dead zone,
+    // delta is the time since the last frame in seconds
+    pub fn follow(&mut self, x: f32, y: f32, delta: f32) {
+        let (goal_x, goal_y) = dead_zone_goal(self.position, (x, y), self.dead_zone);
+
+        let amount = if self.smoothing > 0.0 { 1.0 - (-self.smoothing * delta).exp() } else { 1.0 };
+        let (position_x, position_y) = self.position;
+        self.set_position(
+            position_x + (goal_x - position_x) * amount,
+            position_y + (goal_y - position_y) * amount,
+        );
+    }
+
+    // add trauma, the shake grows with the square of the trauma
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    // the trauma between 0.0 and 1.0
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    // set the offset and rotation at full trauma
+    // and how much trauma is lost every second
+    pub fn set_shake(&mut self, max_offset: f32, max_rotation: f32, decay: f32) {
+        self.max_shake = (max_offset, max_rotation);
+        self.trauma_decay = decay.max(0.0);
+    }
+
+    // advance the shake, call this once every frame
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta;
+        self.trauma = (self.trauma - self.trauma_decay * delta).max(0.0);
+
+        let shake = self.trauma * self.trauma;
+        let (max_offset, max_rotation) = self.max_shake;
+        self.shake = (
+            max_offset * shake * noise(self.time, 0.0),
+            max_offset * shake * noise(self.time, 1.0),
+            max_rotation * shake * noise(self.time, 2.0),
+        );
+        self.refresh();
+    }
+
+    // convert a point on the screen, e.g. the mouse, into the world
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (center_x, center_y) = self.center();
+        let (position_x, position_y) = self.shaken_position();
+        let x = position_x + (x - center_x) / self.zoom;
+        let y = position_y + (y - center_y) / self.zoom;
+        rotate_point(x, y, position_x, position_y, self.shaken_rotation())
+    }
+
+    // convert a point of the world into a point on the screen
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let (center_x, center_y) = self.center();
+        let (position_x, position_y) = self.shaken_position();
+        let (x, y) = rotate_point(x, y, position_x, position_y, -self.shaken_rotation());
+        (
+            center_x + (x - position_x) * self.zoom,
+            center_y + (y - position_y) * self.zoom,
+        )
+    }
+
+    // the part of the world that can be seen, without the shake
+    pub fn visible_area(&self) -> Bounds {
+        let (half_width, half_height) = self.half_view();
+        (self.position.0 - half_width, self.position.1 - half_height, 2.0 * half_width, 2.0 * half_height)
+    }
+
+    // the camera as a Transform
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    // keep the position in bounds and build the transform
+    fn refresh(&mut self) {
+        if let Some(bounds) = self.bounds {
+            self.position = clamp_to_bounds(self.position, self.half_view(), bounds);
+        }
+
+        // screen = center + zoom * rotate(world - position),
+        // as a transform: translate * rotate * scale
+        let (center_x, center_y) = self.center();
+        let (position_x, position_y) = self.shaken_position();
+        let rotation = -self.shaken_rotation();
+        let (x, y) = rotate_point(position_x * self.zoom, position_y * self.zoom, 0.0, 0.0, rotation);
+
+        let mut transform = Transform::new();
+        transform.set(center_x - x, center_y - y, 0.0);
+        transform.set_rotation(rotation, 0.0, 0.0, 1.0);
+        transform.set_scale(self.zoom, self.zoom, 1.0);
+        self.transform = transform;
+    }
+
+    // the center of the viewport on the screen
+    fn center(&self) -> (f32, f32) {
+        let (x, y, width, height) = self.viewport;
+        (x + width / 2.0, y + height / 2.0)
+    }
+
+    // half the width and height of the world that can be seen
+    fn half_view(&self) -> (f32, f32) {
+        let (_, _, width, height) = self.viewport;
+        let (sin, cos) = self.rotation.sin_cos();
+        (
+            (cos.abs() * width + sin.abs() * height) / 2.0 / self.zoom,
+            (sin.abs() * width + cos.abs() * height) / 2.0 / self.zoom,
+        )
+    }
+
+    fn shaken_position(&self) -> (f32, f32) {
+        (self.position.0 + self.shake.0, self.position.1 + self.shake.1)
+    }
+
+    fn shaken_rotation(&self) -> f32 {
+        self.rotation + self.shake.2
+    }
+}
+
+impl Deref for Camera2D {
+    type Target = Transform;
+
+    // lets a Camera2D be passed where a camera Transform is expected
+    fn deref(&self) -> &Transform {
+        &self.transform
+    }
+}
+
+// the position that puts the target on the edge of the dead zone
+fn dead_zone_goal(position: (f32, f32), target: (f32, f32), dead_zone: (f32, f32)) -> (f32, f32) {
+    let axis = |position: f32, target: f32, size: f32| {
+        let half = size / 2.0;
+        if target > position + half {
+            target - half
+        } else if target < position - half {
+            target + half
+        } else {
+            position
+        }
+    };
+
+    (
+        axis(position.0, target.0, dead_zone.0),
+        axis(position.1, target.1, dead_zone.1),
+    )
+}
+
+// keep a view with half width and height inside bounds
+fn clamp_to_bounds(position: (f32, f32), half_view: (f32, f32), bounds: Bounds) -> (f32, f32) {
+    let (x, y, width, height) = bounds;
+    let axis = |position: f32, half: f32, start: f32, size: f32| {
+        if 2.0 * half >= size {
+            start + size / 2.0
+        } else {
+            position.clamp(start + half, start + size - half)
+        }
+    };
+
+    (
+        axis(position.0, half_view.0, x, width),
+        axis(position.1, half_view.1, y, height),
+    )
+}
+
+// smooth noise between -1.0 and 1.0,
+// the seed gives every axis its own pattern
+fn noise(time: f32, seed: f32) -> f32 {
+    let t = time * 20.0 + seed * 17.3;
+    (t.sin() + (t * 2.3 + 1.7).sin() * 0.5 + (t * 4.1 + 4.2).sin() * 0.25) / 1.75
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01
+    }
+
+    #[test]
+    fn test_screen_to_world() {
+        let mut camera = Camera2D::new(800.0, 600.0);
+        assert!(close((10.0, 20.0), camera.screen_to_world(10.0, 20.0)));
+
+        camera.set_position(1000.0, 1000.0);
+        camera.set_zoom(2.0);
+        camera.set_rotation(0.5);
+        assert!(close((1000.0, 1000.0), camera.screen_to_world(400.0, 300.0)));
+        // 100 pixels right of the center are 50 units away in the world
+        let (x, y) = camera.screen_to_world(500.0, 300.0);
+        assert!(((x - 1000.0).hypot(y - 1000.0) - 50.0).abs() < 0.01);
+
+        let world = camera.screen_to_world(123.0, 456.0);
+        assert!(close((123.0, 456.0), camera.world_to_screen(world.0, world.1)));
+
+        // the transform does the same as world_to_screen
+        let point = camera.matrix() * nalgebra_glm::vec4(world.0, world.1, 0.0, 1.0);
+        assert!(close((123.0, 456.0), (point.x, point.y)));
+    }
+
+    #[test]
+    fn test_zoom_at() {
+        let mut camera = Camera2D::new(800.0, 600.0);
+        let before = camera.screen_to_world(100.0, 100.0);
+        camera.zoom_at(100.0, 100.0, 3.0);
+        assert!(close(before, camera.screen_to_world(100.0, 100.0)));
+    }
+
+    #[test]
+    fn test_follow() {
+        assert_eq!((0.0, 0.0), dead_zone_goal((0.0, 0.0), (10.0, -5.0), (40.0, 40.0)));
+        assert_eq!((30.0, -5.0), dead_zone_goal((0.0, 0.0), (50.0, -25.0), (40.0, 40.0)));
+
+        let mut camera = Camera2D::new(800.0, 600.0);
+        camera.set_position(0.0, 0.0);
+        camera.follow(100.0, 50.0, 0.016);
+        assert_eq!((100.0, 50.0), camera.position());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut camera = Camera2D::new(800.0, 600.0);
+        camera.set_bounds(Some((0.0, 0.0, 2000.0, 400.0)));
+        camera.set_position(-100.0, 100.0);
+        // the left edge stays in bounds, the bounds are not as high as the view
+        assert_eq!((400.0, 200.0), camera.position());
+        assert_eq!((0.0, -100.0, 800.0, 600.0), camera.visible_area());
+    }
+
+    #[test]
+    fn test_shake() {
+        let mut camera = Camera2D::new(800.0, 600.0);
+        camera.add_trauma(2.0);
+        assert_eq!(1.0, camera.trauma());
+        camera.update(0.25);
+        assert_eq!(0.75, camera.trauma());
+        camera.update(10.0);
+        assert_eq!(0.0, camera.trauma());
+        // without trauma the camera does not move
+        assert!(close((400.0, 300.0), camera.world_to_screen(400.0, 300.0)));
+    }
+}
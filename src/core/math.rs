@@ -2,4 +2,6 @@ pub mod mvp;
 pub mod transform;
 pub mod collision;
 pub mod pathfinding;
-pub mod linear;
\ No newline at end of file
+pub mod linear;
+pub mod triangulate;
+pub mod culling;
\ No newline at end of file
@@ -24,6 +24,15 @@ pub fn point_in_circle(x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) -> bool
     d < radius
 }
 
+/// rotates a point around an origin,
+/// the angle is in radians and clockwise on the screen
+pub fn rotate_point(point_x: f32, point_y: f32, origin_x: f32, origin_y: f32, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    let x = point_x - origin_x;
+    let y = point_y - origin_y;
+    (origin_x + x * cos - y * sin, origin_y + x * sin + y * cos)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +64,13 @@ mod tests {
         assert_eq!(true, point_in_circle(4.0, 4.0, cirlce_x, circle_y, circle_r));
         assert_eq!(false, point_in_circle(15.0, 15.0, cirlce_x, circle_y, circle_r));
     }
+
+    #[test]
+    fn test_rotate_point() {
+        let (x, y) = rotate_point(10.0, 0.0, 0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        assert!(x.abs() < 0.0001 && (y - 10.0).abs() < 0.0001);
+
+        let (x, y) = rotate_point(3.0, 2.0, 3.0, 2.0, 1.0);
+        assert_eq!((3.0, 2.0), (x, y));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use crate::core::math::mvp;
+use crate::core::math::transform::Transform;
+use crate::core::shader::data::ObjectData;
+use crate::core::window::Window;
+
+// a rectangle with x, y, width and height
+pub type Bounds = (f32, f32, f32, f32);
+
+/// the smallest rectangle around all points
+pub fn point_bounds(points: &[(f32, f32)]) -> Bounds {
+    if points.is_empty() {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+
+    let mut min = (f32::MAX, f32::MAX);
+    let mut max = (f32::MIN, f32::MIN);
+    for &(x, y) in points {
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+
+    (min.0, min.1, max.0 - min.0, max.1 - min.1)
+}
+
+/// the part of the model space that can be seen on the screen,
+/// it uses the same projection, camera and model transform as the shaders
+pub fn visible_area(window: &Window, camera: &Transform, model: &Transform) -> Bounds {
+    let mvp = mvp::ortho(window) * camera.matrix() * model.matrix();
+    let inverse = nalgebra_glm::inverse(&mvp);
+
+    // the corners of the screen in normalized device coordinates
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+    let mut min = (f32::MAX, f32::MAX);
+    let mut max = (f32::MIN, f32::MIN);
+
+    for (x, y) in corners {
+        let point = inverse * nalgebra_glm::vec4(x, y, 0.0, 1.0);
+        let (x, y) = (point.x / point.w, point.y / point.w);
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+
+    (min.0, min.1, max.0 - min.0, max.1 - min.1)
+}
+
+/// the space an instance covers including its rotation and shadow
+pub fn instance_bounds(object_data: &ObjectData) -> Bounds {
+    let (x, y) = object_data.offset;
+    let (width, height) = object_data.dim;
+    let margin = object_data.shadow.margin();
+
+    if object_data.rotation == 0.0 {
+        return (x - margin, y - margin, width + 2.0 * margin, height + 2.0 * margin);
+    }
+
+    // a rotated instance stays in the circle around its pivot
+    // that goes through the corner that is the furthest away
+    let (pivot_x, pivot_y) = object_data.pivot_position();
+    let radius = pivot_x.max(width - pivot_x).hypot(pivot_y.max(height - pivot_y)) + margin;
+    (x + pivot_x - radius, y + pivot_y - radius, 2.0 * radius, 2.0 * radius)
+}
+
+/// true if two rectangles overlap or touch
+pub fn overlaps(a: Bounds, b: Bounds) -> bool {
+    a.0 <= b.0 + b.2 && b.0 <= a.0 + a.2 && a.1 <= b.1 + b.3 && b.1 <= a.1 + a.3
+}
+
+/*
+a SpatialGrid sorts items into square cells
+so finding the items in an area only looks at the cells
+the area touches instead of every item
+*/
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    bounds: Vec<Bounds>,
+}
+
+impl SpatialGrid {
+    // create an empty grid with square cells of cell_size
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(1.0),
+            cells: HashMap::new(),
+            bounds: vec![],
+        }
+    }
+
+    // the size of a cell
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    // add an item with its bounds, items are numbered in the order they are added
+    pub fn insert(&mut self, bounds: Bounds) -> usize {
+        let item = self.bounds.len();
+        self.bounds.push(bounds);
+
+        let ((x1, y1), (x2, y2)) = self.cell_range(bounds);
+        for x in x1..=x2 {
+            for y in y1..=y2 {
+                self.cells.entry((x, y)).or_default().push(item);
+            }
+        }
+
+        item
+    }
+
+    // move an item to new bounds, it keeps its number
+    pub fn update(&mut self, item: usize, bounds: Bounds) {
+        let ((x1, y1), (x2, y2)) = self.cell_range(self.bounds[item]);
+        for x in x1..=x2 {
+            for y in y1..=y2 {
+                if let Some(items_in_cell) = self.cells.get_mut(&(x, y)) {
+                    items_in_cell.retain(|other| *other != item);
+                    if items_in_cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+
+        self.bounds[item] = bounds;
+        let ((x1, y1), (x2, y2)) = self.cell_range(bounds);
+        for x in x1..=x2 {
+            for y in y1..=y2 {
+                self.cells.entry((x, y)).or_default().push(item);
+            }
+        }
+    }
+
+    // remove all items
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.clear();
+    }
+
+    // the number of items
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    // true if there are no items
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    // all items that overlap an area sorted from first to last
+    pub fn query(&self, area: Bounds) -> Vec<usize> {
+        let mut items = vec![];
+
+        let ((x1, y1), (x2, y2)) = self.cell_range(area);
+        // a huge area looks at every item instead of every cell
+        let cells = (x2 - x1 + 1) as i64 * (y2 - y1 + 1) as i64;
+        if cells > self.cells.len() as i64 {
+            for items_in_cell in self.cells.values() {
+                items.extend(items_in_cell.iter().filter(|item| overlaps(self.bounds[**item], area)));
+            }
+        } else {
+            for x in x1..=x2 {
+                for y in y1..=y2 {
+                    if let Some(items_in_cell) = self.cells.get(&(x, y)) {
+                        items.extend(items_in_cell.iter().filter(|item| overlaps(self.bounds[**item], area)));
+                    }
+                }
+            }
+        }
+
+        // an item can be in more than one cell
+        items.sort_unstable();
+        items.dedup();
+        items
+    }
+
+    // the first and the last cell that bounds touch
+    fn cell_range(&self, bounds: Bounds) -> ((i32, i32), (i32, i32)) {
+        let (x, y, width, height) = bounds;
+        let cell = |value: f32| (value / self.cell_size).floor() as i32;
+        ((cell(x), cell(y)), (cell(x + width), cell(y + height)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_area() {
+        let window = Window::new(800.0, 600.0);
+        let mut camera = Transform::new();
+        let model = Transform::new();
+
+        let (x, y, width, height) = visible_area(&window, &camera, &model);
+        assert!(x.abs() < 0.01 && y.abs() < 0.01);
+        assert!((width - 800.0).abs() < 0.01 && (height - 600.0).abs() < 0.01);
+
+        // moving the camera to the left shows what is on the right
+        camera.set(-100.0, 0.0, 0.0);
+        let (x, _, _, _) = visible_area(&window, &camera, &model);
+        assert!((x - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_instance_bounds() {
+        let object_data = ObjectData {
+            offset: (10.0, 20.0),
+            dim: (30.0, 40.0),
+            ..ObjectData::default()
+        };
+        assert_eq!((10.0, 20.0, 30.0, 40.0), instance_bounds(&object_data));
+
+        // rotated around the center it fits in a circle of radius 25
+        let rotated = ObjectData {
+            rotation: 1.0,
+            ..object_data
+        };
+        assert_eq!((0.0, 15.0, 50.0, 50.0), instance_bounds(&rotated));
+    }
+
+    #[test]
+    fn test_point_bounds() {
+        assert_eq!((-5.0, 2.0, 15.0, 8.0), point_bounds(&[(10.0, 2.0), (-5.0, 10.0), (0.0, 4.0)]));
+        assert_eq!((0.0, 0.0, 0.0, 0.0), point_bounds(&[]));
+    }
+
+    #[test]
+    fn test_spatial_grid() {
+        let mut grid = SpatialGrid::new(64.0);
+        for i in 0..100 {
+            grid.insert((i as f32 * 32.0, 0.0, 16.0, 16.0));
+        }
+        // spans many cells
+        grid.insert((0.0, 0.0, 1000.0, 16.0));
+
+        assert_eq!(vec![2, 3, 100], grid.query((64.0, 0.0, 40.0, 10.0)));
+        assert_eq!(vec![0, 100], grid.query((-10.0, 10.0, 20.0, 2.0)));
+        assert!(grid.query((-50.0, 10.0, 20.0, 2.0)).is_empty());
+        assert!(grid.query((0.0, 500.0, 10.0, 10.0)).is_empty());
+        assert_eq!(101, grid.query((-1.0e6, -1.0e6, 2.0e6, 2.0e6)).len());
+
+        // a moved item is only found at its new place
+        grid.update(2, (0.0, 500.0, 16.0, 16.0));
+        assert_eq!(vec![3, 100], grid.query((64.0, 0.0, 40.0, 10.0)));
+        assert_eq!(vec![2], grid.query((0.0, 500.0, 10.0, 10.0)));
+        assert_eq!(101, grid.len());
+    }
+}
@@ -0,0 +1,281 @@
+/**
+split a polygon into triangles using ear clipping
+
+the outline can be convex or concave, holes are
+polygons inside the outline that are cut out.
+the returned triangles are indices into the points of the
+outline followed by the points of all holes
+*/
+pub fn triangulate(outline: &[(f32, f32)], holes: &[Vec<(f32, f32)>]) -> Result<Vec<[usize; 3]>, String> {
+    if outline.len() < 3 {
+        return Err("A polygon needs at least 3 points.".to_string());
+    }
+
+    // all points, the outline first followed by the holes
+    let mut points = outline.to_vec();
+    for hole in holes {
+        points.extend_from_slice(hole);
+    }
+
+    // the outline goes counter clockwise
+    let mut polygon: Vec<usize> = (0..outline.len()).collect();
+    if signed_area(&points, &polygon) < 0.0 {
+        polygon.reverse();
+    }
+
+    // the holes go clockwise, the hole that reaches
+    // furthest to the right gets merged first
+    let mut hole_indices = vec![];
+    let mut start = outline.len();
+    for hole in holes {
+        if hole.len() < 3 {
+            return Err("A hole needs at least 3 points.".to_string());
+        }
+
+        let mut indices: Vec<usize> = (start..start + hole.len()).collect();
+        if signed_area(&points, &indices) > 0.0 {
+            indices.reverse();
+        }
+
+        start += hole.len();
+        hole_indices.push(indices);
+    }
+
+    hole_indices.sort_by(|a, b| max_x(&points, b).total_cmp(&max_x(&points, a)));
+
+    for hole in hole_indices {
+        polygon = merge_hole(&points, &polygon, &hole)?;
+    }
+
+    clip_ears(&points, polygon)
+}
+
+// remove ears until there is only one triangle left
+fn clip_ears(points: &[(f32, f32)], mut polygon: Vec<usize>) -> Result<Vec<[usize; 3]>, String> {
+    let mut triangles = vec![];
+
+    while polygon.len() > 3 {
+        let len = polygon.len();
+        let mut clipped = false;
+
+        for i in 0..len {
+            let prev = polygon[(i + len - 1) % len];
+            let current = polygon[i];
+            let next = polygon[(i + 1) % len];
+
+            if is_ear(points, &polygon, prev, current, next) {
+                triangles.push([prev, current, next]);
+                polygon.remove(i);
+                clipped = true;
+                break;
+            }
+        }
+
+        if !clipped {
+            // remove points that lie on a straight line
+            // before giving up
+            let straight = (0..len).find(|i| {
+                let prev = points[polygon[(i + len - 1) % len]];
+                let next = points[polygon[(i + 1) % len]];
+                cross(prev, points[polygon[*i]], next).abs() <= f32::EPSILON
+            });
+
+            match straight {
+                Some(i) => {
+                    polygon.remove(i);
+                }
+                None => return Err("Could not triangulate the polygon.".to_string()),
+            }
+        }
+    }
+
+    if polygon.len() == 3 {
+        triangles.push([polygon[0], polygon[1], polygon[2]]);
+    }
+
+    Ok(triangles)
+}
+
+// an ear is a convex corner without any other point inside
+fn is_ear(points: &[(f32, f32)], polygon: &[usize], prev: usize, current: usize, next: usize) -> bool {
+    let (a, b, c) = (points[prev], points[current], points[next]);
+
+    if cross(a, b, c) <= 0.0 {
+        return false;
+    }
+
+    for &i in polygon {
+        let p = points[i];
+        // points of bridges are in the polygon twice
+        if p == a || p == b || p == c {
+            continue;
+        }
+
+        if point_in_triangle(p, a, b, c) {
+            return false;
+        }
+    }
+
+    true
+}
+
+// connect a hole to the outline with a bridge
+// between two points that can see each other
+fn merge_hole(points: &[(f32, f32)], polygon: &[usize], hole: &[usize]) -> Result<Vec<usize>, String> {
+    // the point of the hole that is furthest to the right
+    let (hole_start, &m) = hole
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| points[**a].0.total_cmp(&points[**b].0))
+        .ok_or("A hole needs points.")?;
+    let mp = points[m];
+
+    // cast a ray to the right and find the nearest edge of the outline
+    let mut nearest: Option<(f32, usize)> = None;
+    let len = polygon.len();
+    for i in 0..len {
+        let a = points[polygon[i]];
+        let b = points[polygon[(i + 1) % len]];
+
+        // only edges that cross the ray from top to bottom
+        if (a.1 > mp.1) == (b.1 > mp.1) {
+            continue;
+        }
+
+        let x = a.0 + (mp.1 - a.1) / (b.1 - a.1) * (b.0 - a.0);
+        if x < mp.0 {
+            continue;
+        }
+
+        let closer = match nearest {
+            Some((nearest_x, _)) => x < nearest_x,
+            None => true,
+        };
+
+        if closer {
+            // the end of the edge that is further to the right
+            let candidate = if a.0 > b.0 { i } else { (i + 1) % len };
+            nearest = Some((x, candidate));
+        }
+    }
+
+    let (x, mut bridge) = nearest.ok_or("A hole is not inside the outline.")?;
+    let intersection = (x, mp.1);
+    let candidate = points[polygon[bridge]];
+
+    // a reflex point inside the triangle would block the view,
+    // take the one with the smallest angle to the ray
+    let mut best_angle = f32::MAX;
+    for i in 0..len {
+        let prev = points[polygon[(i + len - 1) % len]];
+        let p = points[polygon[i]];
+        let next = points[polygon[(i + 1) % len]];
+
+        let reflex = cross(prev, p, next) < 0.0;
+        if !reflex || p == candidate {
+            continue;
+        }
+
+        if point_in_triangle(p, mp, intersection, candidate) || point_in_triangle(p, mp, candidate, intersection) {
+            let angle = (p.1 - mp.1).abs().atan2(p.0 - mp.0);
+            if angle < best_angle {
+                best_angle = angle;
+                bridge = i;
+            }
+        }
+    }
+
+    // walk the outline to the bridge, around the hole and back
+    let mut merged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+    merged.extend_from_slice(&polygon[..=bridge]);
+    for i in 0..=hole.len() {
+        merged.push(hole[(hole_start + i) % hole.len()]);
+    }
+    merged.extend_from_slice(&polygon[bridge..]);
+
+    Ok(merged)
+}
+
+// twice the area of a polygon, positive when it goes counter clockwise
+fn signed_area(points: &[(f32, f32)], polygon: &[usize]) -> f32 {
+    let len = polygon.len();
+    let mut area = 0.0;
+
+    for i in 0..len {
+        let (x1, y1) = points[polygon[i]];
+        let (x2, y2) = points[polygon[(i + 1) % len]];
+        area += x1 * y2 - x2 * y1;
+    }
+
+    area
+}
+
+// the x value of the point that is furthest to the right
+fn max_x(points: &[(f32, f32)], polygon: &[usize]) -> f32 {
+    polygon.iter().map(|i| points[*i].0).fold(f32::MIN, f32::max)
+}
+
+// positive if a, b, c turn left
+fn cross(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+// true if p is inside or on the edge of the counter clockwise triangle a, b, c
+fn point_in_triangle(p: (f32, f32), a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the area that is covered by all triangles
+    fn area(points: &[(f32, f32)], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|t| cross(points[t[0]], points[t[1]], points[t[2]]).abs() / 2.0)
+            .sum()
+    }
+
+    #[test]
+    fn test_convex() {
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let triangles = triangulate(&square, &[]).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(area(&square, &triangles), 100.0);
+
+        // the direction of the outline does not matter
+        let mut reversed = square.to_vec();
+        reversed.reverse();
+        assert_eq!(area(&reversed, &triangulate(&reversed, &[]).unwrap()), 100.0);
+    }
+
+    #[test]
+    fn test_concave() {
+        let l = [(0.0, 0.0), (20.0, 0.0), (20.0, 10.0), (10.0, 10.0), (10.0, 20.0), (0.0, 20.0)];
+        let triangles = triangulate(&l, &[]).unwrap();
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(area(&l, &triangles), 300.0);
+    }
+
+    #[test]
+    fn test_holes() {
+        let square = [(0.0, 0.0), (30.0, 0.0), (30.0, 30.0), (0.0, 30.0)];
+        let hole = vec![(10.0, 10.0), (20.0, 10.0), (20.0, 20.0), (10.0, 20.0)];
+        let mut points = square.to_vec();
+        points.extend_from_slice(&hole);
+
+        let triangles = triangulate(&square, &[hole]).unwrap();
+        assert_eq!(triangles.len(), 8);
+        assert_eq!(area(&points, &triangles), 800.0);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(triangulate(&[(0.0, 0.0), (1.0, 1.0)], &[]).is_err());
+
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let outside = vec![(20.0, 20.0), (30.0, 20.0), (30.0, 30.0)];
+        assert!(triangulate(&square, &[outside]).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::core::color::Color;
 use crate::core::mouse::Mouse;
-use crate::core::window::Window;
+use crate::core::window::{Scaling, Window};
 use std::time::Instant;
 
 /*
@@ -16,6 +16,10 @@ pub struct Config {
     pub width: u32,
     pub height: u32,
     pub background_color: Color,
+    // how the game gets scaled into the window
+    pub scaling: Scaling,
+    // the color of the bars a scaling can leave
+    pub letterbox_color: Color,
 }
 
 impl Default for Config {
@@ -26,6 +30,8 @@ impl Default for Config {
             width: 1270,
             height: 700,
             background_color: Color::grey(44),
+            scaling: Scaling::None,
+            letterbox_color: Color::grey(0),
         }
     }
 }
@@ -1,3 +1,4 @@
 pub mod font;
 pub mod image;
+pub mod layout;
 pub mod tilemap;
\ No newline at end of file
@@ -1,4 +1,5 @@
-use rusttype::{point, Scale, PositionedGlyph};
+use crate::core::resource::layout::{image_bounds, layout_text, LayoutOptions, TextLayout};
+use rusttype::{point, Scale};
 use image::{Rgba, ImageBuffer, imageops::flip_vertical};
 
 /*
@@ -14,6 +15,7 @@ docs: https://docs.rs/rusttype/0.9.2/rusttype/index.html
 // impartant is that we dont load the same image multiple time for 
 // performance reseasons
 */
+#[derive(Clone)]
 pub struct Font<'a> {
     font: rusttype::Font<'a>
 }
@@ -34,59 +36,114 @@ impl<'a> Font<'a> {
     }
 
     /*
-    returns an rgba image of a given text
+    returns an rgba image of a given text in a single line,
+    the image is as wide as the text including its spaces
     */
     pub fn snapshot(&self, text: &str, font_size: f32) -> Result<image::RgbaImage, String> {
-        // the font size:
-        let scale = Scale::uniform(font_size);
-        let v_metrics = self.font.v_metrics(scale);
+        self.snapshot_layout(text, &LayoutOptions::new(font_size))
+    }
 
-        // layout the glyph in a line with 20 pixels padding
-        let glyphs: Vec<PositionedGlyph> = self.font
-            .layout(&text, scale, point(0.0, 0.0 + v_metrics.ascent))
-            .collect();
-        
-        // work out the layout size
-        let glyphs_height = (v_metrics.ascent - v_metrics.descent).ceil() as u32;
-        if let Some(glyphs_width) = glyph_width(&glyphs) {
-            // create a new rgba image with some random value 10, which does nothing until you set it to low, than it chrashes
-            // because out of bounds
-            let mut image = ImageBuffer::new(glyphs_width + 10, glyphs_height + 10);
-
-            // loop through the glyphs in the text positioning each one on a line
-            for glyph in glyphs {
-                if let Some(bounding_box) = glyph.pixel_bounding_box() {
-                    // draw the glyph into the image per-pixel using the draw closure
-                    glyph.draw(|x,y,v| {
-                        image.put_pixel(
-                            // offset the position by the glyph bounding box
-                            x + bounding_box.min.x as u32,
-                            y + bounding_box.min.y as u32,
-                            // turn the coverage into an alpha value
-                            Rgba([255, 255, 255, (v * 255.0) as u8]),
-                        )
-                    });
-                }
+    /*
+    returns an rgba image of a given text
+    with newlines, wrapping and alignment,
+    glyphs that hang over the layout make the image bigger
+    */
+    pub fn snapshot_layout(&self, text: &str, options: &LayoutOptions) -> Result<image::RgbaImage, String> {
+        let layout = self.layout(text, options);
+        let (min_x, min_y, max_x, max_y) = self.image_bounds(&layout, options.font_size);
+        let (width, height) = ((max_x - min_x) as u32, (max_y - min_y) as u32);
+        if width == 0 || height == 0 {
+            return Err(format!("cannot create an image of the empty text '{}'", text));
+        }
+
+        let scale = Scale::uniform(options.font_size);
+        let mut image: image::RgbaImage = ImageBuffer::new(width, height);
+
+        for glyph in layout.lines.iter().flat_map(|line| &line.glyphs) {
+            let positioned = self.font.glyph(glyph.c).scaled(scale).positioned(point(glyph.x, glyph.y));
+
+            if let Some(bounding_box) = positioned.pixel_bounding_box() {
+                // draw the glyph into the image per-pixel using the draw closure
+                positioned.draw(|x, y, v| {
+                    // offset the position by the glyph bounding box
+                    // and the ink that hangs over the top left
+                    let x = (x as i32 + bounding_box.min.x - min_x) as u32;
+                    let y = (y as i32 + bounding_box.min.y - min_y) as u32;
+                    // turn the coverage into an alpha value
+                    let alpha = (v * 255.0) as u8;
+                    let pixel = image.get_pixel_mut(x, y);
+                    if alpha > pixel[3] {
+                        *pixel = Rgba([255, 255, 255, alpha]);
+                    }
+                });
             }
+        }
+
+        // flip it vertically because opengl needs them upside down
+        Ok(flip_vertical(&image))
+    }
+
+    // put a text into lines without rendering it
+    pub fn layout(&self, text: &str, options: &LayoutOptions) -> TextLayout {
+        let scale = Scale::uniform(options.font_size);
+        let v_metrics = self.font.v_metrics(scale);
+        let metrics = (v_metrics.ascent, v_metrics.descent, v_metrics.line_gap);
+
+        let advance = |c: char| self.font.glyph(c).scaled(scale).h_metrics().advance_width;
+        let kerning = |a: char, b: char| self.font.pair_kerning(scale, a, b);
+        layout_text(text, options, metrics, advance, kerning)
+    }
 
-            // flip it vertically because opengl needs them upside down
-            let image = flip_vertical(&image);
-            return Ok(image);
-        } 
+    // the width and height of the image of a text in a single line
+    // without rendering it, spaces count
+    pub fn measure(&self, text: &str, font_size: f32) -> (f32, f32) {
+        let layout = self.layout(text, &LayoutOptions::new(font_size));
+        let (min_x, min_y, max_x, max_y) = self.image_bounds(&layout, font_size);
+        ((max_x - min_x) as f32, (max_y - min_y) as f32)
+    }
 
-        Err("could not calculate the glyphs width".to_string())
+    // the pixels of the layout and the ink of all its glyphs
+    fn image_bounds(&self, layout: &TextLayout, font_size: f32) -> (i32, i32, i32, i32) {
+        let scale = Scale::uniform(font_size);
+        let ink = layout.lines.iter().flat_map(|line| &line.glyphs).filter_map(|glyph| {
+            let positioned = self.font.glyph(glyph.c).scaled(scale).positioned(point(glyph.x, glyph.y));
+            positioned.pixel_bounding_box().map(|bounds| (bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y))
+        });
+        image_bounds(layout, ink)
     }
 }
 
-// resolves the results (Options) of operations done on glyphs width calculation
-fn glyph_width(glyphs: &Vec<PositionedGlyph>) -> Option<u32> {
-    if let Some(first) = glyphs.first() {
-        if let Some(last) = glyphs.last() {
-            let max_x = last.pixel_bounding_box()?.max.x;
-            let min_x = first.pixel_bounding_box()?.min.x;
-            return Some((max_x - min_x) as u32);
+/*
+a single glyph rendered on its own, left and top are
+where the image sits relative to the pen on the baseline
+*/
+pub(crate) struct RasterGlyph {
+    pub image: Option<image::RgbaImage>,
+    pub left: f32,
+    pub top: f32,
+}
+
+impl<'a> Font<'a> {
+    // render a single glyph, characters without
+    // pixels like spaces have no image
+    pub(crate) fn rasterize(&self, c: char, font_size: f32) -> RasterGlyph {
+        let glyph = self.font.glyph(c).scaled(Scale::uniform(font_size)).positioned(point(0.0, 0.0));
+
+        let mut raster = RasterGlyph {
+            image: None,
+            left: 0.0,
+            top: 0.0,
+        };
+
+        if let Some(bounding_box) = glyph.pixel_bounding_box() {
+            let mut image = ImageBuffer::new(bounding_box.width() as u32, bounding_box.height() as u32);
+            glyph.draw(|x, y, v| image.put_pixel(x, y, Rgba([255, 255, 255, (v * 255.0) as u8])));
+
+            raster.image = Some(image);
+            raster.left = bounding_box.min.x as f32;
+            raster.top = bounding_box.min.y as f32;
         }
+
+        raster
     }
-    
-    None
-}
\ No newline at end of file
+}
@@ -64,4 +64,38 @@ impl Image {
             width: width as f32, height: height as f32,
         }
     }
+
+    // paste an image into this image,
+    // x and y are from the top left corner
+    pub fn replace(&mut self, x: u32, y: u32, image: &Image) -> Result<(), String> {
+        let (width, height) = (image.data.width(), image.data.height());
+        if x + width > self.data.width() || y + height > self.data.height() {
+            return Err("The image does not fit in at this position.".to_string());
+        }
+
+        // the rows are upside down for opengl
+        let row = self.data.height() - y - height;
+        image::imageops::replace(&mut *self.data, &*image.data, x as i64, row as i64);
+        Ok(())
+    }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace() {
+        let mut image = Image::from(image::RgbaImage::new(4, 4));
+        let red = Image::from(image::RgbaImage::from_pixel(2, 1, image::Rgba([255, 0, 0, 255])));
+
+        // the top row is stored last
+        assert!(image.replace(1, 0, &red).is_ok());
+        let data = image.to_rgba_image();
+        assert_eq!(255, data.get_pixel(1, 3)[0]);
+        assert_eq!(255, data.get_pixel(2, 3)[0]);
+        assert_eq!(0, data.get_pixel(1, 0)[0]);
+
+        assert!(image.replace(3, 3, &red).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,304 @@
+/// how the lines of a text are placed in its width
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+    // stretch the spaces so every line but the last
+    // of a paragraph fills the width
+    Justify,
+}
+
+/// how a text is put into lines
+/// ```rust
+/// let options = LayoutOptions {
+///     max_width: Some(300.0),
+///     align: Align::Center,
+///     line_height: 1.2,
+///     ..LayoutOptions::new(24.0)
+/// };
+///
+/// let layout = font.layout("a long text that gets wrapped", &options);
+/// let (width, height) = font.measure("Score: 100", 24.0);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LayoutOptions {
+    pub font_size: f32,
+    // wrap lines that get wider, None only breaks at newlines
+    pub max_width: Option<f32>,
+    pub align: Align,
+    // the distance of the lines as a multiple of the line height of the font
+    pub line_height: f32,
+    // move pairs of characters like "AV" closer together
+    pub kerning: bool,
+}
+
+impl LayoutOptions {
+    // a single line of font_size with kerning
+    pub fn new(font_size: f32) -> Self {
+        Self {
+            font_size,
+            max_width: None,
+            align: Align::Left,
+            line_height: 1.0,
+            kerning: true,
+        }
+    }
+}
+
+/// a character of a laid out text, x is where the pen
+/// is and y is the baseline of its line
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GlyphPosition {
+    pub c: char,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// a line of a laid out text
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextLine {
+    pub glyphs: Vec<GlyphPosition>,
+    // the start of the line after the alignment
+    pub x: f32,
+    pub baseline: f32,
+    // the width of the line including its spaces
+    pub width: f32,
+}
+
+/// a text in lines with its bounds, everything
+/// starts at the top left of the text
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    // the width of the widest line, or the max width when lines are aligned in it
+    pub width: f32,
+    // from the top of the first line to the bottom of the last
+    pub height: f32,
+}
+
+// the vertical size of a line of a font:
+// ascent, descent (negative) and the gap between lines
+pub(crate) type LineMetrics = (f32, f32, f32);
+
+/*
+put a text into lines, advance is the width of a character
+and kerning the change of the distance between two characters
+
+spaces where a line gets wrapped are dropped,
+spaces at the start and end of the text count
+*/
+pub(crate) fn layout_text<A, K>(text: &str, options: &LayoutOptions, metrics: LineMetrics, advance: A, kerning: K) -> TextLayout
+where
+    A: Fn(char) -> f32,
+    K: Fn(char, char) -> f32,
+{
+    let (ascent, descent, line_gap) = metrics;
+    let measure = |chars: &[char]| line_width(chars, &advance, &kerning, options.kerning);
+
+    // the characters of every line and if it ends a paragraph
+    let mut rows = vec![];
+    for paragraph in text.split('\n') {
+        let chars: Vec<char> = paragraph.trim_end_matches('\r').chars().collect();
+        let wrapped = match options.max_width {
+            Some(max_width) => wrap(&chars, max_width, &measure),
+            None => vec![chars],
+        };
+
+        let count = wrapped.len();
+        rows.extend(wrapped.into_iter().enumerate().map(|(i, row)| (row, i + 1 == count)));
+    }
+
+    let widths: Vec<f32> = rows.iter().map(|(row, _)| measure(row)).collect();
+    let widest = widths.iter().cloned().fold(0.0, f32::max);
+    let width = options.max_width.unwrap_or(widest);
+    let spacing = (ascent - descent + line_gap) * options.line_height;
+
+    let mut lines = vec![];
+    for (i, ((row, last), line_width)) in rows.into_iter().zip(widths).enumerate() {
+        let free = (width - line_width).max(0.0);
+        let spaces = row.iter().filter(|c| **c == ' ').count();
+
+        let (x, stretch) = match options.align {
+            Align::Left => (0.0, 0.0),
+            Align::Center => (free / 2.0, 0.0),
+            Align::Right => (free, 0.0),
+            Align::Justify if !last && spaces > 0 => (0.0, free / spaces as f32),
+            Align::Justify => (0.0, 0.0),
+        };
+
+        let baseline = ascent + i as f32 * spacing;
+        let mut glyphs = vec![];
+        let mut pen = x;
+        for (j, &c) in row.iter().enumerate() {
+            if j > 0 && options.kerning {
+                pen += kerning(row[j - 1], c);
+            }
+
+            glyphs.push(GlyphPosition { c, x: pen, y: baseline });
+            pen += advance(c);
+            if c == ' ' {
+                pen += stretch;
+            }
+        }
+
+        lines.push(TextLine {
+            glyphs,
+            x,
+            baseline,
+            width: line_width + stretch * spaces as f32,
+        });
+    }
+
+    let height = ascent - descent + lines.len().saturating_sub(1) as f32 * spacing;
+    TextLayout { lines, width, height }
+}
+
+// the pixels a laid out text covers, the box of the layout
+// joined with the ink of its glyphs that can hang over it,
+// as min x, min y, max x and max y
+pub(crate) fn image_bounds<I>(layout: &TextLayout, ink: I) -> (i32, i32, i32, i32)
+where
+    I: IntoIterator<Item = (i32, i32, i32, i32)>,
+{
+    let mut bounds = (0, 0, layout.width.ceil() as i32, layout.height.ceil() as i32);
+    for (min_x, min_y, max_x, max_y) in ink {
+        bounds = (bounds.0.min(min_x), bounds.1.min(min_y), bounds.2.max(max_x), bounds.3.max(max_y));
+    }
+    bounds
+}
+
+// the width of the characters of a line
+fn line_width<A, K>(chars: &[char], advance: &A, kerning: &K, use_kerning: bool) -> f32
+where
+    A: Fn(char) -> f32,
+    K: Fn(char, char) -> f32,
+{
+    let mut width = 0.0;
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && use_kerning {
+            width += kerning(chars[i - 1], c);
+        }
+        width += advance(c);
+    }
+    width
+}
+
+// break a paragraph into lines that are at most max_width wide,
+// words that are too long on their own are broken anywhere
+fn wrap<M>(chars: &[char], max_width: f32, measure: &M) -> Vec<Vec<char>>
+where
+    M: Fn(&[char]) -> f32,
+{
+    let mut rows = vec![];
+    let mut line: Vec<char> = vec![];
+    let mut has_word = false;
+    let mut spaces: Vec<char> = vec![];
+
+    for word in chars.split_inclusive(|c| *c == ' ') {
+        // a word and the spaces after it
+        let end = word.iter().rposition(|c| *c != ' ').map_or(0, |i| i + 1);
+        let (word, trailing) = word.split_at(end);
+        if word.is_empty() {
+            spaces.extend_from_slice(trailing);
+            continue;
+        }
+
+        let mut candidate = [&line[..], &spaces[..], word].concat();
+        if has_word && measure(&candidate) > max_width {
+            // the word starts the next line without the spaces before it
+            rows.push(std::mem::take(&mut line));
+            candidate = word.to_vec();
+        }
+
+        while candidate.len() > 1 && measure(&candidate) > max_width {
+            let fits = (1..candidate.len()).rev().find(|n| measure(&candidate[..*n]) <= max_width).unwrap_or(1);
+            rows.push(candidate[..fits].to_vec());
+            candidate = candidate[fits..].to_vec();
+        }
+
+        line = candidate;
+        has_word = true;
+        spaces = trailing.to_vec();
+    }
+
+    line.extend_from_slice(&spaces);
+    rows.push(line);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every character is 10 wide, "AV" is 2 closer
+    fn layout(text: &str, options: &LayoutOptions) -> TextLayout {
+        let kerning = |a, b| if (a, b) == ('A', 'V') { -2.0 } else { 0.0 };
+        layout_text(text, options, (8.0, -2.0, 2.0), |_| 10.0, kerning)
+    }
+
+    // the characters of every line
+    fn rows(layout: &TextLayout) -> Vec<String> {
+        layout.lines.iter().map(|line| line.glyphs.iter().map(|glyph| glyph.c).collect()).collect()
+    }
+
+    #[test]
+    fn test_measure() {
+        let options = LayoutOptions::new(10.0);
+        // spaces at the start and end count
+        let text = layout("  ab ", &options);
+        assert_eq!((50.0, 10.0), (text.width, text.height));
+
+        let text = layout("AV\nabc\r\n", &options);
+        assert_eq!(vec!["AV", "abc", ""], rows(&text));
+        assert_eq!(18.0, text.lines[0].width);
+        // lines are 12 apart
+        assert_eq!((30.0, 34.0), (text.width, text.height));
+        assert_eq!(20.0, text.lines[1].baseline);
+
+        // ink that hangs over the left and the bottom makes the image bigger
+        let text = layout("ab", &options);
+        assert_eq!((0, 0, 20, 10), image_bounds(&text, vec![(1, 2, 9, 8)]));
+        assert_eq!((-2, 0, 20, 13), image_bounds(&text, vec![(-2, 1, 8, 9), (11, 3, 19, 13)]));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let options = LayoutOptions {
+            max_width: Some(75.0),
+            ..LayoutOptions::new(10.0)
+        };
+
+        let text = layout("the quick brown fox", &options);
+        assert_eq!(vec!["the", "quick", "brown", "fox"], rows(&text));
+        let text = layout("a bc def", &options);
+        assert_eq!(vec!["a bc", "def"], rows(&text));
+        // too long words get broken
+        let text = layout("abcdefghijk x", &options);
+        assert_eq!(vec!["abcdefg", "hijk x"], rows(&text));
+        assert_eq!(75.0, text.width);
+    }
+
+    #[test]
+    fn test_align() {
+        let options = LayoutOptions {
+            max_width: Some(100.0),
+            align: Align::Right,
+            ..LayoutOptions::new(10.0)
+        };
+        let text = layout("ab", &options);
+        assert_eq!(80.0, text.lines[0].glyphs[0].x);
+
+        let center = LayoutOptions { align: Align::Center, ..options };
+        assert_eq!(40.0, layout("ab", &center).lines[0].x);
+
+        // the spaces of every line but the last get wider
+        let justify = LayoutOptions { align: Align::Justify, ..options };
+        let text = layout("a b c d e f g h", &justify);
+        assert_eq!(vec!["a b c d e", "f g h"], rows(&text));
+        assert_eq!(100.0, text.lines[0].width);
+        assert_eq!(90.0, text.lines[0].glyphs[8].x);
+        assert_eq!(20.0, text.lines[1].glyphs[2].x);
+    }
+}
@@ -70,6 +70,12 @@ impl TileData {
             }
         }
     }
+
+    // the left, top, right and bottom
+    // of the tile in texture coordinates
+    pub(crate) fn region(&self) -> (f32, f32, f32, f32) {
+        (self.x1, self.y1, self.x2, self.y2)
+    }
 }
 
 pub struct TileMap {
@@ -85,6 +85,16 @@ impl Seed {
         num
     }
 
+    /*
+    get the next 16 bits in the seed as a f32
+    from 0.0 to 1.0
+    */
+    pub fn next_f32(&mut self) -> f32 {
+        let high = self.next_u8() as u16;
+        let low = self.next_u8() as u16;
+        ((high << 8) | low) as f32 / u16::MAX as f32
+    }
+
     /*
     roll a u8 number. if its below max return true, 
     the closer to 255 the more likely true is  
@@ -130,4 +140,25 @@ impl Seed {
 
         self.bytes = Seed::hash(&iteration);
     } 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_f32() {
+        let mut a = Seed::from_str("test");
+        let mut b = Seed::from_str("test");
+
+        // two bytes of the seed, the first is the high byte
+        let value = a.next_f32();
+        let (high, low) = (b.next_u8() as f32, b.next_u8() as f32);
+        assert_eq!((high * 256.0 + low) / 65535.0, value);
+
+        for _ in 0..1000 {
+            let value = a.next_f32();
+            assert!((0.0..=1.0).contains(&value));
+        }
+    }
+}
@@ -1,8 +1,18 @@
+pub mod blend;
+pub mod clip;
 pub mod data;
+pub mod glyphs;
+pub mod handle;
 pub mod instanced;
+pub mod layer;
+pub mod lighting;
+pub mod mesh;
 pub mod object;
+pub mod particles;
+pub mod postprocess;
 pub mod program;
 pub mod shader;
+pub mod target;
 
 use gl::types::*;
 use std::ffi::CString;
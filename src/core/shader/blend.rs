@@ -0,0 +1,115 @@
+use gl::types::*;
+use std::cell::Cell;
+
+/// how the colors of a Shader get combined
+/// with the colors that are already drawn
+/// ```rust
+/// let mut particles = InstancedShader::circle().unwrap();
+/// particles.set_blend(BlendMode::Additive);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BlendMode {
+    // draw over the background with the alpha of the color,
+    // this is the blending that init sets up
+    #[default]
+    Alpha,
+    // like alpha but the colors are already multiplied with their alpha
+    Premultiplied,
+    // add the colors, used for lights and particles
+    Additive,
+    // darken the background with the colors
+    Multiply,
+    // brighten the background with the colors
+    Screen,
+    // replace the background, alpha is ignored
+    Opaque,
+}
+
+thread_local! {
+    // the blend mode that is set on the graphics card,
+    // init sets up alpha blending
+    static CURRENT: Cell<BlendMode> = const { Cell::new(BlendMode::Alpha) };
+}
+
+impl BlendMode {
+    // set the blend mode on the graphics card
+    // and return the mode that was set before,
+    // nothing is sent when the mode is already set
+    pub(crate) fn apply(&self) -> BlendMode {
+        let previous = CURRENT.with(|current| current.replace(*self));
+        if previous == *self {
+            return previous;
+        }
+
+        unsafe {
+            match self {
+                BlendMode::Opaque => gl::Disable(gl::BLEND),
+                _ => gl::Enable(gl::BLEND),
+            }
+
+            match self {
+                BlendMode::Alpha => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
+                BlendMode::Premultiplied => gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA),
+                BlendMode::Additive => gl::BlendFunc(gl::SRC_ALPHA, gl::ONE),
+                BlendMode::Multiply => gl::BlendFunc(gl::DST_COLOR, gl::ONE_MINUS_SRC_ALPHA),
+                BlendMode::Screen => gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_COLOR),
+                BlendMode::Opaque => (),
+            }
+        }
+
+        previous
+    }
+}
+
+/*
+the blending of the graphics card at one moment,
+used to put it back after the lights were drawn
+*/
+pub(crate) struct BlendState {
+    enabled: bool,
+    src_rgb: GLint,
+    dst_rgb: GLint,
+    src_alpha: GLint,
+    dst_alpha: GLint,
+}
+
+impl BlendState {
+    // read the blending that is active right now
+    pub(crate) fn current() -> Self {
+        let mut state = Self {
+            enabled: false,
+            src_rgb: 0,
+            dst_rgb: 0,
+            src_alpha: 0,
+            dst_alpha: 0,
+        };
+
+        unsafe {
+            state.enabled = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+            gl::GetIntegerv(gl::BLEND_SRC_RGB, &mut state.src_rgb);
+            gl::GetIntegerv(gl::BLEND_DST_RGB, &mut state.dst_rgb);
+            gl::GetIntegerv(gl::BLEND_SRC_ALPHA, &mut state.src_alpha);
+            gl::GetIntegerv(gl::BLEND_DST_ALPHA, &mut state.dst_alpha);
+        }
+
+        state
+    }
+
+    // put the blending back
+    pub(crate) fn restore(&self) {
+        unsafe {
+            if self.enabled {
+                gl::Enable(gl::BLEND);
+            } else {
+                gl::Disable(gl::BLEND);
+            }
+
+            gl::BlendFuncSeparate(
+                self.src_rgb as GLenum,
+                self.dst_rgb as GLenum,
+                self.src_alpha as GLenum,
+                self.dst_alpha as GLenum,
+            );
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use gl::types::*;
+use crate::core::math::culling::Bounds;
+use crate::core::math::transform::Transform;
+use crate::core::project::Draw;
+use crate::core::shader::layer::Drawable;
+
+/// only draw inside of a rect, the rect is moved by the camera
+/// like every other object, clips can be nested
+/// ```rust
+/// clip::rect(&draw, &camera, (20.0, 20.0, 200.0, 400.0), || {
+///     list.draw(&draw, &scroll_camera)
+/// })?;
+/// ```
+pub fn rect<F>(draw: &Draw, camera: &Transform, rect: Bounds, f: F) -> Result<(), String>
+where
+    F: FnOnce() -> Result<(), String>,
+{
+    // the rect is in the units of the bound viewport,
+    // which is not the window inside of a viewport or a render target
+    let mut viewport = [0; 4];
+    unsafe {
+        gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+    }
+    let rect = scissor_box(viewport, (draw.window.width, draw.window.height), screen_rect(camera, rect));
+
+    // a clip inside of a clip only draws where both are
+    let scissor = ScissorState::current();
+    let clip = if scissor.enabled { intersect(scissor.rect, rect) } else { rect };
+
+    unsafe {
+        gl::Enable(gl::SCISSOR_TEST);
+        gl::Scissor(clip[0], clip[1], clip[2], clip[3]);
+    }
+
+    let result = f();
+    scissor.restore();
+    result
+}
+
+/// only draw where the mask is, the mask can be any
+/// Shader and is not visible itself, masks can be nested
+/// ```rust
+/// let mut minimap_shape = Shader::circle().unwrap();
+/// minimap_shape.set_dim(200.0, 200.0);
+///
+/// clip::mask(&draw, &camera, &mut minimap_shape, || {
+///     minimap.draw(&draw, &camera)
+/// })?;
+/// ```
+pub fn mask<F>(draw: &Draw, camera: &Transform, mask: &mut dyn Drawable, f: F) -> Result<(), String>
+where
+    F: FnOnce() -> Result<(), String>,
+{
+    // every mask adds one to the stencil buffer where it is drawn,
+    // so a mask inside of a mask only counts where the outer mask is
+    let stencil = StencilState::current();
+    let depth = if stencil.enabled { stencil.reference } else { 0 };
+
+    unsafe {
+        gl::Enable(gl::STENCIL_TEST);
+        gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+        gl::StencilFunc(gl::EQUAL, depth, 0xFF);
+        gl::StencilOp(gl::KEEP, gl::KEEP, gl::INCR);
+    }
+    let drawn = mask.draw(draw, camera);
+
+    unsafe {
+        gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+        gl::StencilFunc(gl::EQUAL, depth + 1, 0xFF);
+        gl::StencilOp(gl::KEEP, gl::KEEP, gl::KEEP);
+    }
+    let result = drawn.and_then(|_| f());
+
+    // draw the mask again to take it out of the stencil buffer
+    unsafe {
+        gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+        gl::StencilOp(gl::KEEP, gl::KEEP, gl::DECR);
+    }
+    let removed = mask.draw(draw, camera);
+
+    unsafe {
+        gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+    }
+    stencil.restore();
+    result.and(removed)
+}
+
+/*
+the scissor test of the graphics card at one moment,
+used to put it back after a clip or a viewport
+*/
+pub(crate) struct ScissorState {
+    enabled: bool,
+    rect: [GLint; 4],
+}
+
+impl ScissorState {
+    // read the scissor test that is active right now
+    pub(crate) fn current() -> Self {
+        let mut state = Self {
+            enabled: false,
+            rect: [0; 4],
+        };
+
+        unsafe {
+            state.enabled = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+            gl::GetIntegerv(gl::SCISSOR_BOX, state.rect.as_mut_ptr());
+        }
+
+        state
+    }
+
+    // put the scissor test back
+    pub(crate) fn restore(&self) {
+        unsafe {
+            if self.enabled {
+                gl::Enable(gl::SCISSOR_TEST);
+            } else {
+                gl::Disable(gl::SCISSOR_TEST);
+            }
+
+            let [x, y, width, height] = self.rect;
+            gl::Scissor(x, y, width, height);
+        }
+    }
+}
+
+/*
+the stencil test of the graphics card at one moment,
+used to put it back after a mask or the lights
+*/
+pub(crate) struct StencilState {
+    enabled: bool,
+    function: GLint,
+    reference: GLint,
+    value_mask: GLint,
+    fail: GLint,
+    depth_fail: GLint,
+    pass: GLint,
+}
+
+impl StencilState {
+    // read the stencil test that is active right now
+    pub(crate) fn current() -> Self {
+        let mut state = Self {
+            enabled: false,
+            function: 0,
+            reference: 0,
+            value_mask: 0,
+            fail: 0,
+            depth_fail: 0,
+            pass: 0,
+        };
+
+        unsafe {
+            state.enabled = gl::IsEnabled(gl::STENCIL_TEST) == gl::TRUE;
+            gl::GetIntegerv(gl::STENCIL_FUNC, &mut state.function);
+            gl::GetIntegerv(gl::STENCIL_REF, &mut state.reference);
+            gl::GetIntegerv(gl::STENCIL_VALUE_MASK, &mut state.value_mask);
+            gl::GetIntegerv(gl::STENCIL_FAIL, &mut state.fail);
+            gl::GetIntegerv(gl::STENCIL_PASS_DEPTH_FAIL, &mut state.depth_fail);
+            gl::GetIntegerv(gl::STENCIL_PASS_DEPTH_PASS, &mut state.pass);
+        }
+
+        state
+    }
+
+    // put the stencil test back
+    pub(crate) fn restore(&self) {
+        unsafe {
+            if self.enabled {
+                gl::Enable(gl::STENCIL_TEST);
+            } else {
+                gl::Disable(gl::STENCIL_TEST);
+            }
+
+            gl::StencilFunc(self.function as GLenum, self.reference, self.value_mask as GLuint);
+            gl::StencilOp(self.fail as GLenum, self.depth_fail as GLenum, self.pass as GLenum);
+        }
+    }
+}
+
+// the rect the camera moves a rect to in the units of the window,
+// a rotated rect gets the rect around it
+fn screen_rect(camera: &Transform, rect: Bounds) -> Bounds {
+    let (x, y, width, height) = rect;
+    let matrix = camera.matrix();
+    let corners = [(x, y), (x + width, y), (x + width, y + height), (x, y + height)];
+
+    let mut min = (f32::MAX, f32::MAX);
+    let mut max = (f32::MIN, f32::MIN);
+    for (x, y) in corners {
+        let point = matrix * nalgebra_glm::vec4(x, y, 0.0, 1.0);
+        min = (min.0.min(point.x), min.1.min(point.y));
+        max = (max.0.max(point.x), max.1.max(point.y));
+    }
+
+    (min.0, min.1, max.0 - min.0, max.1 - min.1)
+}
+
+// the scissor box of a rect in the units of the window,
+// when the window is drawn to the viewport, y from the bottom
+pub(crate) fn scissor_box(viewport: [GLint; 4], window: (f32, f32), rect: Bounds) -> [GLint; 4] {
+    let (x, y, width, height) = rect;
+    let [viewport_x, viewport_y, viewport_width, viewport_height] = viewport;
+    let scale_x = viewport_width as f32 / window.0;
+    let scale_y = viewport_height as f32 / window.1;
+
+    let top = (viewport_y + viewport_height) as f32;
+    let left = (viewport_x as f32 + x * scale_x).round();
+    let bottom = (top - (y + height) * scale_y).round();
+    let right = (viewport_x as f32 + (x + width) * scale_x).round();
+    let upper = (top - y * scale_y).round();
+    [left as GLint, bottom as GLint, (right - left) as GLint, (upper - bottom) as GLint]
+}
+
+// the part two scissor boxes have in common
+fn intersect(a: [GLint; 4], b: [GLint; 4]) -> [GLint; 4] {
+    let x = a[0].max(b[0]);
+    let y = a[1].max(b[1]);
+    let right = (a[0] + a[2]).min(b[0] + b[2]);
+    let top = (a[1] + a[3]).min(b[1] + b[3]);
+    [x, y, (right - x).max(0), (top - y).max(0)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_rect() {
+        let mut camera = Transform::new();
+        camera.set(-50.0, 10.0, 0.0);
+        camera.set_scale(2.0, 2.0, 1.0);
+        assert_eq!((-30.0, 30.0, 40.0, 20.0), screen_rect(&camera, (10.0, 10.0, 20.0, 10.0)));
+    }
+
+    #[test]
+    fn test_scissor_box() {
+        // a 400x200 window drawn 2x into a viewport of a bigger target
+        let viewport = [100, 50, 800, 400];
+        assert_eq!([140, 370, 80, 40], scissor_box(viewport, (400.0, 200.0), (20.0, 20.0, 40.0, 20.0)));
+        assert_eq!([0, 0, 400, 200], scissor_box([0, 0, 400, 200], (400.0, 200.0), (0.0, 0.0, 400.0, 200.0)));
+    }
+
+    #[test]
+    fn test_intersect() {
+        assert_eq!([50, 0, 50, 100], intersect([0, 0, 100, 100], [50, -20, 200, 200]));
+        // boxes that don't touch leave nothing
+        assert_eq!([200, 0, 0, 100], intersect([0, 0, 100, 100], [200, 0, 100, 100]));
+    }
+}
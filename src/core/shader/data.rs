@@ -7,6 +7,10 @@ use crate::core::shader::object::TextureCoordinate;
 /// let mut od = ObjectData::default();
 /// od.dim = (128.0, 128.0);
 /// od.offset = (450.0, 210.0);
+/// od.corner_radius = [16.0, 16.0, 0.0, 0.0];
+/// od.stroke_width = 2.0;
+/// od.stroke_color = Color::grey(255);
+/// od.rotation = std::f32::consts::FRAC_PI_4;
 /// ```
 #[derive(Clone, Copy)]
 pub struct ObjectData {
@@ -15,6 +19,31 @@ pub struct ObjectData {
     pub opacity: f32,
     pub offset: (f32, f32),
     pub texcoord: TextureCoordinate,
+    // radius of the top left, top right, bottom right
+    // and bottom left corner of rects in pixels
+    pub corner_radius: [f32; 4],
+    // width of the outline of rects and circles in pixels
+    pub stroke_width: f32,
+    pub stroke_color: Color,
+    // the hole of a circle as a fraction of its radius,
+    // 0.0 is a disc 0.9 a thin ring
+    pub inner_radius: f32,
+    // start and end angle of a circle in radians
+    // clockwise from the right
+    pub arc: (f32, f32),
+    pub shadow: Shadow,
+    // fills rects and circles instead of the color
+    pub gradient: Gradient,
+    // angle in radians, clockwise around the pivot
+    pub rotation: f32,
+    // the point to rotate around as a fraction of dim,
+    // (0.5, 0.5) is the center
+    pub pivot: (f32, f32),
+    // mirror the instance horizontally or vertically
+    pub flip_x: bool,
+    pub flip_y: bool,
+    // how textures mix with the color
+    pub tint: TintMode,
 }
 
 impl Default for ObjectData {
@@ -31,6 +60,251 @@ impl Default for ObjectData {
                 1.0, 1.0,            
                 1.0, 0.0,
             ],
+            corner_radius: [0.0; 4],
+            stroke_width: 0.0,
+            stroke_color: Color::default(),
+            inner_radius: 0.0,
+            arc: (0.0, std::f32::consts::TAU),
+            shadow: Shadow::default(),
+            gradient: Gradient::default(),
+            rotation: 0.0,
+            pivot: (0.5, 0.5),
+            flip_x: false,
+            flip_y: false,
+            tint: TintMode::None,
         }
     }
-}
\ No newline at end of file
+}
+
+impl ObjectData {
+    // the pivot in pixels from the offset
+    pub fn pivot_position(&self) -> (f32, f32) {
+        let (width, height) = self.dim;
+        (self.pivot.0 * width, self.pivot.1 * height)
+    }
+
+    // the rotation attribute of the vertex shaders,
+    // angle, pivot and the flip as bits (1 x, 2 y)
+    pub(crate) fn rotation_data(&self) -> [f32; 4] {
+        let flip = self.flip_x as u8 + 2 * self.flip_y as u8;
+        [self.rotation, self.pivot.0, self.pivot.1, flip as f32]
+    }
+}
+
+/// how the color of a texture gets mixed with the color of the ObjectData
+/// ```rust
+/// // flash the sprite white when it gets hit
+/// sprite.set_tint(&Color::grey(255), TintMode::Replace);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TintMode {
+    // draw the texture as it is
+    None,
+    // multiply the texture with the color
+    Multiply,
+    // add the color to the texture
+    Additive,
+    // use the color but keep the alpha of the texture
+    Replace,
+    // blend from the texture to the color by an amount from 0.0 to 1.0
+    Lerp(f32),
+}
+
+impl TintMode {
+    // the mode and the amount for the texture shader
+    pub(crate) fn data(&self) -> [f32; 2] {
+        match self {
+            TintMode::None => [0.0, 0.0],
+            TintMode::Multiply => [1.0, 0.0],
+            TintMode::Additive => [2.0, 0.0],
+            TintMode::Replace => [3.0, 0.0],
+            TintMode::Lerp(amount) => [4.0, amount.clamp(0.0, 1.0)],
+        }
+    }
+}
+
+/// a drop shadow behind rects and circles
+/// ```rust
+/// let shadow = Shadow::new(4.0, 6.0, 8.0, &Color::grey(0));
+/// rect.set_shadow(&shadow);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Shadow {
+    pub offset: (f32, f32),
+    pub blur: f32,
+    pub color: Color,
+}
+
+impl Shadow {
+    // create a shadow that is moved by x and y offset
+    // and blurred by blur pixels
+    pub fn new(x_offset: f32, y_offset: f32, blur: f32, color: &Color) -> Self {
+        Self {
+            offset: (x_offset, y_offset),
+            blur,
+            color: *color,
+        }
+    }
+
+    // the space around a shape that the shadow needs,
+    // enough for the offset in any rotation
+    pub fn margin(&self) -> f32 {
+        if self.color.a <= 0.0 {
+            return 0.0;
+        }
+
+        self.blur + self.offset.0.hypot(self.offset.1)
+    }
+}
+
+impl Default for Shadow {
+    // an invisible shadow
+    fn default() -> Self {
+        Self {
+            offset: (0.0, 0.0),
+            blur: 0.0,
+            color: Color::default(),
+        }
+    }
+}
+
+// the direction of a gradient
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GradientKind {
+    None,
+    // the angle in radians, 0.0 goes from left to right
+    Linear(f32),
+    // from the center to the edge
+    Radial,
+}
+
+/// a color gradient with up to 4 color stops
+/// ```rust
+/// let mut gradient = Gradient::linear(std::f32::consts::FRAC_PI_2, &Color::rgb(0, 0, 80), &Color::rgb(120, 180, 255));
+/// gradient.add_stop(0.5, &Color::rgb(40, 90, 200)).unwrap();
+/// sky.set_gradient(&gradient);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Gradient {
+    kind: GradientKind,
+    stops: [(f32, Color); 4],
+    count: usize,
+}
+
+impl Gradient {
+    // the maximum number of color stops
+    pub const MAX_STOPS: usize = 4;
+
+    // create a linear gradient from one color to another
+    // the angle is in radians, 0.0 goes from left to right
+    pub fn linear(angle: f32, from: &Color, to: &Color) -> Self {
+        Self::two_stops(GradientKind::Linear(angle), from, to)
+    }
+
+    // create a radial gradient from the center to the edge
+    pub fn radial(inner: &Color, outer: &Color) -> Self {
+        Self::two_stops(GradientKind::Radial, inner, outer)
+    }
+
+    // add a color stop at a position between 0.0 and 1.0
+    pub fn add_stop(&mut self, position: f32, color: &Color) -> Result<(), String> {
+        if self.count >= Self::MAX_STOPS {
+            return Err(format!("A gradient can only have {} color stops.", Self::MAX_STOPS));
+        }
+
+        self.stops[self.count] = (position.clamp(0.0, 1.0), *color);
+        self.count += 1;
+
+        // keep the stops in order of their position
+        self.stops[..self.count].sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(())
+    }
+
+    // remove all color stops
+    pub fn clear(&mut self) {
+        self.count = 0;
+    }
+
+    // get the direction of the gradient
+    pub fn kind(&self) -> GradientKind {
+        self.kind
+    }
+
+    // get the color stops in order of their position
+    pub fn stops(&self) -> Vec<(f32, Color)> {
+        self.stops[..self.count].to_vec()
+    }
+
+    // the gradient as shader data, 4 colors followed by
+    // 4 positions, the kind, the angle and the number of stops
+    pub(crate) fn data(&self) -> [f32; 24] {
+        let mut data = [0.0; 24];
+
+        for (i, (position, color)) in self.stops.iter().enumerate() {
+            data[i * 4..i * 4 + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+            data[16 + i] = *position;
+        }
+
+        let (kind, angle) = match self.kind {
+            _ if self.count == 0 => (0.0, 0.0),
+            GradientKind::None => (0.0, 0.0),
+            GradientKind::Linear(angle) => (1.0, angle),
+            GradientKind::Radial => (2.0, 0.0),
+        };
+
+        data[20] = kind;
+        data[21] = angle;
+        data[22] = self.count as f32;
+        data
+    }
+
+    // a gradient with a stop at the start and the end
+    fn two_stops(kind: GradientKind, from: &Color, to: &Color) -> Self {
+        let mut stops = [(0.0, Color::default()); 4];
+        stops[0] = (0.0, *from);
+        stops[1] = (1.0, *to);
+        Self { kind, stops, count: 2 }
+    }
+}
+
+impl Default for Gradient {
+    // no gradient, the color is used
+    fn default() -> Self {
+        Self {
+            kind: GradientKind::None,
+            stops: [(0.0, Color::default()); 4],
+            count: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tint() {
+        assert_eq!([0.0, 0.0], TintMode::None.data());
+        assert_eq!([3.0, 0.0], TintMode::Replace.data());
+        assert_eq!([4.0, 1.0], TintMode::Lerp(1.5).data());
+    }
+
+    #[test]
+    fn test_gradient() {
+        let mut gradient = Gradient::radial(&Color::grey(255), &Color::grey(0));
+        assert!(gradient.add_stop(0.25, &Color::rgb(255, 0, 0)).is_ok());
+        assert!(gradient.add_stop(2.0, &Color::rgb(0, 255, 0)).is_ok());
+        assert!(gradient.add_stop(0.5, &Color::rgb(0, 0, 255)).is_err());
+
+        let positions: Vec<f32> = gradient.stops().iter().map(|stop| stop.0).collect();
+        assert_eq!(positions, vec![0.0, 0.25, 1.0, 1.0]);
+
+        let data = gradient.data();
+        assert_eq!(&data[4..8], &[1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(&data[20..23], &[2.0, 0.0, 4.0]);
+
+        assert_eq!(Gradient::default().data()[20], 0.0);
+        gradient.clear();
+        assert_eq!(gradient.data()[22], 0.0);
+    }
+}
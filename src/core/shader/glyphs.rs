@@ -0,0 +1,517 @@
+use crate::core::color::Color;
+use crate::core::math::mvp;
+use crate::core::math::transform::Transform;
+use crate::core::project::Draw;
+use crate::core::resource::font::Font;
+use crate::core::resource::layout::LayoutOptions;
+use crate::core::shader::object::{Buffer, Filter, InstanceBuffer, ObjectState, TextureBuffer, TextureOptions, VertexArray};
+use crate::core::shader::program::Program;
+use crate::core::shader::Shader;
+use image::{imageops, ImageBuffer};
+use gl::types::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// every glyph is an instance of the same rectangle,
+// the texture coordinates are in pixels of the atlas
+// so they stay right when the atlas grows
+const VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in vec2 position;
+    layout (location = 1) in vec4 rect;
+    layout (location = 2) in vec4 source;
+
+    uniform mat4 projection;
+    uniform mat4 view;
+    uniform mat4 model;
+    uniform sampler2D sampler;
+
+    out vec2 oTexCoord;
+
+    void main() {
+        vec2 offset_position = rect.xy + position * rect.zw;
+        gl_Position = projection * view * model * vec4(offset_position, 0.0, 1.0);
+        oTexCoord = (source.xy + position * source.zw) / vec2(textureSize(sampler, 0));
+    }
+"#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+    uniform vec4 text_color;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(text_color.rgb, texture(sampler, oTexCoord).a * text_color.a);
+
+        // invisible pixels are not drawn so they don't count for stencil masks
+        if(color.a < 0.004) {
+            discard;
+        }
+    }
+"#;
+
+// the size of a new atlas in pixels
+const ATLAS_SIZE: u32 = 256;
+
+// the empty pixels between glyphs so
+// smooth filtering doesn't pick up the neighbour
+const PADDING: u32 = 1;
+
+// x, y, width and height of the glyph on the screen
+// and x, y, width and height in the atlas in pixels
+type GlyphData = [f32; 8];
+
+// a glyph in the atlas
+#[derive(Clone, Copy)]
+struct AtlasGlyph {
+    // x, y, width and height in the atlas, None for glyphs without pixels
+    rect: Option<(f32, f32, f32, f32)>,
+    left: f32,
+    top: f32,
+}
+
+/*
+places rects in rows from the top to the bottom,
+the atlas grows in height when a rect doesn't fit
+anymore and in width for rects that are too wide,
+but never beyond the biggest texture of the graphics card
+*/
+struct Shelves {
+    width: u32,
+    height: u32,
+    max_size: u32,
+    x: u32,
+    y: u32,
+    row: u32,
+}
+
+impl Shelves {
+    // start with an empty atlas
+    fn new(width: u32, height: u32, max_size: u32) -> Self {
+        Self {
+            width,
+            height,
+            max_size,
+            x: 0,
+            y: 0,
+            row: 0,
+        }
+    }
+
+    // the position of a rect of width and height,
+    // an error if the atlas would get too big
+    fn place(&mut self, width: u32, height: u32) -> Result<(u32, u32), String> {
+        let (width, height) = (width + PADDING, height + PADDING);
+
+        let mut atlas_width = self.width;
+        while width > atlas_width {
+            atlas_width *= 2;
+        }
+
+        // start a new row
+        let (mut x, mut y, mut row) = (self.x, self.y, self.row);
+        if x + width > atlas_width {
+            x = 0;
+            y += row;
+            row = 0;
+        }
+
+        let mut atlas_height = self.height;
+        while y + height > atlas_height {
+            atlas_height *= 2;
+        }
+
+        if atlas_width > self.max_size || atlas_height > self.max_size {
+            return Err(format!("The glyph atlas cannot grow beyond {0}x{0} pixels.", self.max_size));
+        }
+
+        self.width = atlas_width;
+        self.height = atlas_height;
+        self.x = x + width;
+        self.y = y;
+        self.row = row.max(height);
+        Ok((x, y))
+    }
+}
+
+/// A texture of the glyphs of fonts that gets filled on demand,
+/// all GlyphTexts of an atlas share its texture
+///
+/// glyphs are rendered at whole pixel font sizes so animated sizes
+/// reuse them, glyphs are never removed and laying out a text
+/// returns an error once the atlas would be bigger than the
+/// biggest texture of the graphics card
+/// ```rust
+/// let font = Font::new_ttf("assets/roboto.ttf").unwrap();
+/// let mut atlas = GlyphAtlas::new();
+/// let roboto = atlas.add_font(&font);
+///
+/// let mut fps = GlyphText::new(&atlas, roboto, 24.0, &Color::grey(255)).unwrap();
+/// fps.transform.set(10.0, 10.0, 0.0);
+///
+/// // every frame, only the instances change
+/// fps.set_text(&mut atlas, &format!("{} fps", draw.performance.fps())).unwrap();
+/// fps.draw(&draw, &camera).unwrap();
+/// ```
+pub struct GlyphAtlas<'a> {
+    fonts: Vec<Font<'a>>,
+    // the glyphs by font, character and font size in whole pixels
+    glyphs: HashMap<(usize, char, u32), AtlasGlyph>,
+    shelves: Shelves,
+    // a copy of the texture so it can grow
+    image: image::RgbaImage,
+    texture_buffer: Rc<TextureBuffer>,
+}
+
+impl<'a> GlyphAtlas<'a> {
+    // create an empty atlas without fonts
+    pub fn new() -> Self {
+        let mut max_size: GLint = 0;
+        unsafe {
+            gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max_size);
+        }
+
+        let atlas = Self {
+            fonts: vec![],
+            glyphs: HashMap::new(),
+            shelves: Shelves::new(ATLAS_SIZE, ATLAS_SIZE, (max_size as u32).max(ATLAS_SIZE)),
+            image: ImageBuffer::new(ATLAS_SIZE, ATLAS_SIZE),
+            texture_buffer: Rc::new(TextureBuffer::new()),
+        };
+
+        atlas.upload();
+        atlas
+    }
+
+    // add a font and get its index for GlyphTexts
+    pub fn add_font(&mut self, font: &Font<'a>) -> usize {
+        self.fonts.push(font.clone());
+        self.fonts.len() - 1
+    }
+
+    // the number of fonts
+    pub fn font_count(&self) -> usize {
+        self.fonts.len()
+    }
+
+    // the number of glyphs in the atlas
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    // true if no glyph was rendered yet
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    // the width and height of the atlas in pixels
+    pub fn dim(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+
+    // the glyph quads of a text and its width and height
+    pub(crate) fn layout(&mut self, font: usize, options: &LayoutOptions, text: &str) -> Result<(Vec<GlyphData>, (f32, f32)), String> {
+        let layout = self.font(font)?.layout(text, options);
+
+        let mut quads = vec![];
+        for position in layout.lines.iter().flat_map(|line| &line.glyphs) {
+            let glyph = self.glyph(font, position.c, options.font_size)?;
+
+            if let Some((x, y, width, height)) = glyph.rect {
+                // glyphs start on whole pixels to stay sharp
+                let left = position.x.round() + glyph.left;
+                let top = position.y.round() + glyph.top;
+                quads.push([left, top, width, height, x, y, width, height]);
+            }
+        }
+
+        Ok((quads, (layout.width, layout.height)))
+    }
+
+    // the texture of the atlas
+    pub(crate) fn texture_buffer(&self) -> Rc<TextureBuffer> {
+        self.texture_buffer.clone()
+    }
+
+    // font i or an error
+    fn font(&self, font: usize) -> Result<&Font<'a>, String> {
+        self.fonts
+            .get(font)
+            .ok_or_else(|| format!("Font with index '{}' not found.", font))
+    }
+
+    // get a glyph from the atlas,
+    // missing glyphs get rendered into it
+    fn glyph(&mut self, font: usize, c: char, font_size: f32) -> Result<AtlasGlyph, String> {
+        // every font size in between two pixels shares the glyphs
+        let font_size = font_size.round().max(1.0);
+        let key = (font, c, font_size as u32);
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return Ok(*glyph);
+        }
+
+        let raster = self.fonts[font].rasterize(c, font_size);
+        let mut glyph = AtlasGlyph {
+            rect: None,
+            left: raster.left,
+            top: raster.top,
+        };
+
+        if let Some(image) = raster.image {
+            let (width, height) = image.dimensions();
+            let (x, y) = self.shelves.place(width, height)?;
+
+            if (self.shelves.width, self.shelves.height) != self.image.dimensions() {
+                // copy everything into a bigger atlas,
+                // the glyphs keep their pixel positions
+                let mut bigger = ImageBuffer::new(self.shelves.width, self.shelves.height);
+                imageops::replace(&mut bigger, &self.image, 0, 0);
+                imageops::replace(&mut bigger, &image, x as i64, y as i64);
+                self.image = bigger;
+                self.upload();
+            } else {
+                // the rows of the atlas are uploaded from the top,
+                // so the y of the image is the y of the texture
+                imageops::replace(&mut self.image, &image, x as i64, y as i64);
+                self.texture_buffer.set_sub_data(&image, x as i32, y as i32);
+            }
+
+            glyph.rect = Some((x as f32, y as f32, width as f32, height as f32));
+        }
+
+        self.glyphs.insert(key, glyph);
+        Ok(glyph)
+    }
+
+    // send the whole atlas to the graphics card
+    fn upload(&self) {
+        let options = TextureOptions {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            ..TextureOptions::default()
+        };
+
+        self.texture_buffer.set_data(&self.image, &options);
+    }
+}
+
+impl Default for GlyphAtlas<'_> {
+    // an empty atlas without fonts
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/*
+A GlyphText draws a text with the glyphs of a GlyphAtlas,
+every glyph is an instance of a single draw call so changing
+the text only changes the instances and not the texture
+*/
+pub struct GlyphText {
+    pub transform: Transform,
+    program: Program,
+    vertex_array: VertexArray,
+    model_buffer: Buffer, // the buffer needs to stay alive
+    instance_buffer: InstanceBuffer<GlyphData>,
+    texture_buffer: Rc<TextureBuffer>,
+    font: usize,
+    options: LayoutOptions,
+    color: Color,
+    text: String,
+    dim: (f32, f32),
+    state: ObjectState,
+}
+
+impl GlyphText {
+    // create an empty text with font i of the atlas
+    pub fn new(atlas: &GlyphAtlas, font: usize, font_size: f32, color: &Color) -> Result<Self, String> {
+        atlas.font(font)?;
+
+        let model_data: [f32; 4 * 2] = [
+            1.0, 0.0, // top right 0
+            0.0, 0.0, // top left 1
+            0.0, 1.0, // bottom left 2
+            1.0, 1.0, // bottom right 3
+        ];
+
+        let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
+        let fragment_shader = Shader::new(FRAGMENT_SHADER_SOURCE, gl::FRAGMENT_SHADER)?;
+
+        let mut text = Self {
+            transform: Transform::new(),
+            program: Program::new(&vertex_shader, &fragment_shader)?,
+            vertex_array: VertexArray::new(),
+            model_buffer: Buffer::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW),
+            instance_buffer: InstanceBuffer::new(),
+            texture_buffer: atlas.texture_buffer(),
+            font,
+            options: LayoutOptions::new(font_size),
+            color: *color,
+            text: String::new(),
+            dim: (0.0, 0.0),
+            state: ObjectState::Ok,
+        };
+
+        unsafe {
+            text.vertex_array.bind();
+
+            // create a new buffer for our model data
+            text.model_buffer.set_data(&model_data.to_vec());
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 8, std::ptr::null()); // position
+            gl::EnableVertexAttribArray(0);
+
+            // create a new buffer for the glyphs
+            text.instance_buffer.load();
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, 32, std::ptr::null()); // rect
+            gl::VertexAttribPointer(2, 4, gl::FLOAT, gl::FALSE, 32, 16 as *const _); // source
+            gl::VertexAttribDivisor(1, 1);
+            gl::VertexAttribDivisor(2, 1);
+            gl::EnableVertexAttribArray(1);
+            gl::EnableVertexAttribArray(2);
+        }
+
+        Ok(text)
+    }
+
+    // change the text, glyphs that are not
+    // in the atlas yet get rendered into it
+    pub fn set_text(&mut self, atlas: &mut GlyphAtlas, text: &str) -> Result<(), String> {
+        if self.text == text {
+            return Ok(());
+        }
+
+        self.text = text.to_string();
+        self.relayout(atlas)
+    }
+
+    // get the text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // change the font size of the text
+    pub fn set_font_size(&mut self, atlas: &mut GlyphAtlas, font_size: f32) -> Result<(), String> {
+        self.options.font_size = font_size;
+        self.relayout(atlas)
+    }
+
+    // get the font size
+    pub fn font_size(&self) -> f32 {
+        self.options.font_size
+    }
+
+    // change how the text is put into lines,
+    // e.g. wrapping and alignment
+    pub fn set_layout(&mut self, atlas: &mut GlyphAtlas, options: &LayoutOptions) -> Result<(), String> {
+        self.options = *options;
+        self.relayout(atlas)
+    }
+
+    // get how the text is put into lines
+    pub fn layout(&self) -> LayoutOptions {
+        self.options
+    }
+
+    // set the color of the text
+    pub fn set_color(&mut self, color: &Color) {
+        self.color = *color;
+    }
+
+    // get the color of the text
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    // the width and height of the text
+    pub fn dim(&self) -> (f32, f32) {
+        self.dim
+    }
+
+    // the width of the text
+    pub fn width(&self) -> f32 {
+        self.dim.0
+    }
+
+    // the height of the text
+    pub fn height(&self) -> f32 {
+        self.dim.1
+    }
+
+    // draw the text to the screen
+    pub fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        // upload the glyphs if needed
+        match self.state {
+            ObjectState::Reload => {
+                self.instance_buffer.upload();
+                self.state = ObjectState::Ok;
+            }
+            ObjectState::Ok => (),
+        }
+
+        // create the mvp (model view projection) matrixes
+        let projection = mvp::ortho(&draw.window);
+        let view = camera.matrix();
+        let model = self.transform.matrix();
+        let color = self.color;
+
+        unsafe {
+            self.program.bind();
+            self.vertex_array.bind();
+            self.texture_buffer.bind();
+
+            let projection_location = self.program.get_uniform_location("projection")?;
+            let view_location = self.program.get_uniform_location("view")?;
+            let model_location = self.program.get_uniform_location("model")?;
+            let color_location = self.program.get_uniform_location("text_color")?;
+            gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
+            gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
+            gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
+            gl::Uniform4f(color_location, color.r, color.g, color.b, color.a);
+            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.instance_buffer.draw_count() as i32);
+        }
+
+        Ok(())
+    }
+
+    // put the glyphs of the text in place again
+    fn relayout(&mut self, atlas: &mut GlyphAtlas) -> Result<(), String> {
+        let (quads, dim) = atlas.layout(self.font, &self.options, &self.text)?;
+        self.instance_buffer.replace(quads);
+        self.dim = dim;
+        self.state = ObjectState::Reload;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shelves() {
+        let mut shelves = Shelves::new(16, 16, 64);
+        assert_eq!(Ok((0, 0)), shelves.place(7, 5));
+        assert_eq!(Ok((8, 0)), shelves.place(7, 3));
+        // the row is full, the next one starts below the highest
+        assert_eq!(Ok((0, 6)), shelves.place(4, 4));
+        assert_eq!((16, 16), (shelves.width, shelves.height));
+
+        // too low for the atlas
+        assert_eq!(Ok((5, 6)), shelves.place(4, 12));
+        assert_eq!((16, 32), (shelves.width, shelves.height));
+
+        // too wide for the atlas, the wider atlas has space in the row
+        assert_eq!(Ok((10, 6)), shelves.place(20, 2));
+        assert_eq!(32, shelves.width);
+
+        // bigger than the graphics card allows, nothing changes
+        assert!(shelves.place(80, 2).is_err());
+        assert!(shelves.place(4, 60).is_err());
+        assert_eq!((32, 32), (shelves.width, shelves.height));
+        assert_eq!(Ok((31, 6)), shelves.place(0, 0));
+    }
+}
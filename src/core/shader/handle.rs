@@ -0,0 +1,197 @@
+use crate::core::shader::instanced::InstancedShader;
+
+/// points to an instance of an [InstancedShader](InstancedShader)
+/// and stays valid when other instances are removed
+/// ```rust
+/// let bullet = bullets.add(&od);
+/// bullets.set_offset(bullet, 10.0, 20.0)?;
+/// bullets.remove_handle(bullet)?;
+/// // the handle is stale now
+/// assert!(bullets.get(bullet).is_err());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InstanceHandle {
+    slot: usize,
+    generation: u32,
+}
+
+/// finds an instance of an [InstancedShader](InstancedShader),
+/// either by its index or by its handle
+/// ```rust
+/// bullets.set_color(0, &Color::rgb(255, 0, 0))?;
+/// bullets.set_color(bullet, &Color::rgb(255, 0, 0))?;
+/// ```
+pub trait InstanceRef {
+    // the index of the instance, an error when
+    // the index is out of bounds or the handle is stale
+    fn instance_index(self, shader: &InstancedShader) -> Result<usize, String>;
+}
+
+impl InstanceRef for usize {
+    fn instance_index(self, shader: &InstancedShader) -> Result<usize, String> {
+        shader.index_oob(self)?;
+        Ok(self)
+    }
+}
+
+impl InstanceRef for InstanceHandle {
+    fn instance_index(self, shader: &InstancedShader) -> Result<usize, String> {
+        shader.index(self)
+    }
+}
+
+// a slot can be reused by a new instance,
+// the generation tells old handles apart
+struct Slot {
+    generation: u32,
+    index: Option<usize>,
+}
+
+/*
+Handles maps the handles to the current index of their instance
+
+removing an instance by handle swaps the last instance
+into its place, so only one other instance moves
+*/
+pub(crate) struct Handles {
+    slots: Vec<Slot>,
+    // slots that have no instance
+    free: Vec<usize>,
+    // the slot of every instance
+    owners: Vec<usize>,
+}
+
+impl Handles {
+    // no handles
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+            owners: vec![],
+        }
+    }
+
+    // create a handle for an instance that was added at the end
+    pub(crate) fn insert(&mut self) -> InstanceHandle {
+        let index = self.owners.len();
+
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot].index = Some(index);
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    index: Some(index),
+                });
+                self.slots.len() - 1
+            }
+        };
+
+        self.owners.push(slot);
+        InstanceHandle {
+            slot,
+            generation: self.slots[slot].generation,
+        }
+    }
+
+    // the index of the instance of a handle
+    pub(crate) fn index(&self, handle: InstanceHandle) -> Result<usize, String> {
+        match self.slots.get(handle.slot) {
+            Some(Slot { generation, index: Some(index) }) if *generation == handle.generation => Ok(*index),
+            _ => Err(format!("The instance handle {:?} is stale.", handle)),
+        }
+    }
+
+    // the handle of instance i
+    pub(crate) fn handle(&self, i: usize) -> Option<InstanceHandle> {
+        let slot = *self.owners.get(i)?;
+        Some(InstanceHandle {
+            slot,
+            generation: self.slots[slot].generation,
+        })
+    }
+
+    // remove the instance of a handle by moving the last instance
+    // into its place, returns the index that was removed
+    pub(crate) fn swap_remove(&mut self, handle: InstanceHandle) -> Result<usize, String> {
+        let index = self.index(handle)?;
+        self.release(handle.slot);
+
+        self.owners.swap_remove(index);
+        if let Some(&moved) = self.owners.get(index) {
+            self.slots[moved].index = Some(index);
+        }
+
+        Ok(index)
+    }
+
+    // remove instance i, all instances after it move one to the front
+    pub(crate) fn remove(&mut self, i: usize) {
+        let slot = self.owners.remove(i);
+        self.release(slot);
+
+        for (index, &slot) in self.owners.iter().enumerate().skip(i) {
+            self.slots[slot].index = Some(index);
+        }
+    }
+
+    // remove all instances, every handle gets stale
+    pub(crate) fn clear(&mut self) {
+        for slot in std::mem::take(&mut self.owners) {
+            self.release(slot);
+        }
+    }
+
+    // make the handles of a slot stale and reuse it later
+    fn release(&mut self, slot: usize) {
+        let slot_data = &mut self.slots[slot];
+        slot_data.index = None;
+        slot_data.generation = slot_data.generation.wrapping_add(1);
+        self.free.push(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_remove() {
+        let mut handles = Handles::new();
+        let a = handles.insert();
+        let b = handles.insert();
+        let c = handles.insert();
+
+        assert_eq!(Ok(0), handles.swap_remove(a));
+        // c moved into the place of a
+        assert_eq!(Ok(0), handles.index(c));
+        assert_eq!(Ok(1), handles.index(b));
+        assert!(handles.index(a).is_err());
+        assert!(handles.swap_remove(a).is_err());
+
+        // the slot of a is reused with a new generation
+        let d = handles.insert();
+        assert_eq!(Ok(2), handles.index(d));
+        assert!(handles.index(a).is_err());
+        assert_eq!(Some(d), handles.handle(2));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut handles = Handles::new();
+        let a = handles.insert();
+        let b = handles.insert();
+        let c = handles.insert();
+
+        handles.remove(0);
+        assert!(handles.index(a).is_err());
+        assert_eq!(Ok(0), handles.index(b));
+        assert_eq!(Ok(1), handles.index(c));
+
+        handles.clear();
+        assert!(handles.index(b).is_err());
+        assert_eq!(None, handles.handle(0));
+    }
+}
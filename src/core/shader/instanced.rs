@@ -1,11 +1,23 @@
 use crate::core::color::Color;
 use crate::core::math::collision;
+use crate::core::math::culling::{self, SpatialGrid};
 use crate::core::resource::font::Font;
-use crate::core::shader::object::TextureCoordinate;
+use crate::core::shader::object::{TextureCoordinate, TextureOptions};
 use crate::core::shader::object::{circle::Circle, rect::Rect, text::Text, texture::Texture};
+use crate::core::shader::object::line::{Line, Polyline};
+use crate::core::shader::object::nineslice::{NineSlice, NineSliceData};
+use crate::core::resource::tilemap::{TileData, TileMap};
+use crate::core::shader::object::polygon::{Polygon, PolygonData};
 use crate::core::shader::object::{Object, ObjectState};
+use crate::core::shader::target::RenderTarget;
+use crate::core::shader::blend::BlendMode;
+use crate::core::shader::handle::{Handles, InstanceHandle, InstanceRef};
+use crate::core::shader::data::{Gradient, Shadow, TintMode};
 use crate::{Draw, Image, ObjectData, Transform};
 
+// the size of the cells of the culling grid in pixels
+const DEFAULT_CELL_SIZE: f32 = 256.0;
+
 // this enum will
 // help in not rendering something because you forgot the
 // load method
@@ -25,6 +37,11 @@ pub struct InstancedShader {
     pub transform: Transform,
     object_data: Vec<ObjectData>,
     object: Box<dyn Object>,
+    blend: BlendMode,
+    handles: Handles,
+    culling: bool,
+    grid: SpatialGrid,
+    grid_dirty: bool,
     state: InstancedComponentState,
 }
 
@@ -32,43 +49,68 @@ impl InstancedShader {
     // create a new rect InstancedShader
     pub fn rect() -> Result<Self, String> {
         let rect = Rect::new();
-
-        let component = Self {
-            object: Box::new(rect),
-            object_data: vec![],
-            transform: Transform::default(),
-            state: InstancedComponentState::NotLoaded,
-        };
-
-        Ok(component)
+        Ok(Self::from_object(Box::new(rect)))
     }
 
     // create a new circle InstancedShader
     pub fn circle() -> Result<Self, String> {
         let circle = Circle::new();
+        Ok(Self::from_object(Box::new(circle)))
+    }
 
-        let component = Self {
-            object: Box::new(circle),
-            object_data: vec![],
-            transform: Transform::default(),
-            state: InstancedComponentState::NotLoaded,
-        };
+    // create a new line InstancedShader
+    // every instance is a polyline
+    pub fn line() -> Result<Self, String> {
+        let line = Line::new();
+        Ok(Self::from_object(Box::new(line)))
+    }
+
+    // create a new polygon InstancedShader
+    // every instance is a polygon
+    pub fn polygon() -> Result<Self, String> {
+        let polygon = Polygon::new();
+        Ok(Self::from_object(Box::new(polygon)))
+    }
 
-        Ok(component)
+    // create a new polygon InstancedShader
+    // where every polygon is filled with the image
+    pub fn textured_polygon(image: &Image) -> Result<Self, String> {
+        let polygon = Polygon::textured(image);
+        Ok(Self::from_object(Box::new(polygon)))
     }
 
     // create a new texture InstancedShader
     pub fn texture(image: &Image) -> Result<Self, String> {
         let texture = Texture::new(image);
+        Ok(Self::from_object(Box::new(texture)))
+    }
 
-        let component = Self {
-            object: Box::new(texture),
-            object_data: vec![],
-            transform: Transform::default(),
-            state: InstancedComponentState::NotLoaded,
-        };
+    // create a new texture InstancedShader
+    // that is sampled with options
+    pub fn texture_with_options(image: &Image, options: &TextureOptions) -> Result<Self, String> {
+        let texture = Texture::with_options(image, options);
+        Ok(Self::from_object(Box::new(texture)))
+    }
+
+    // create a new nine slice InstancedShader
+    // the borders keep their size at every dim
+    pub fn nine_slice(image: &Image, slices: &NineSliceData) -> Result<Self, String> {
+        let nine_slice = NineSlice::new(image, slices, &TextureOptions::default());
+        Ok(Self::from_object(Box::new(nine_slice)))
+    }
 
-        Ok(component)
+    // create a new nine slice InstancedShader
+    // out of a tile of a tilemap
+    pub fn nine_slice_tile(tilemap: &TileMap, tile: &TileData, slices: &NineSliceData) -> Result<Self, String> {
+        let nine_slice = NineSlice::region(&tilemap.image(), tile.region(), slices, &TextureOptions::default());
+        Ok(Self::from_object(Box::new(nine_slice)))
+    }
+
+    // create a new texture InstancedShader that displays
+    // everything that was drawn into the RenderTarget
+    pub fn render_target(target: &RenderTarget) -> Result<Self, String> {
+        let texture = Texture::from_buffer(target.texture_buffer());
+        Ok(Self::from_object(Box::new(texture)))
     }
 
     // create a new text InstancedShader
@@ -78,24 +120,122 @@ impl InstancedShader {
         let image = Image::from(image);
 
         let text = Text::new(&image)?;
+        Ok(Self::from_object(Box::new(text)))
+    }
 
-        let component = Self {
-            object: Box::new(text),
+    // an InstancedShader without instances that draws the object
+    fn from_object(object: Box<dyn Object>) -> Self {
+        Self {
+            object,
             object_data: vec![],
             transform: Transform::default(),
+            blend: BlendMode::default(),
+            handles: Handles::new(),
+            culling: false,
+            grid: SpatialGrid::new(DEFAULT_CELL_SIZE),
+            grid_dirty: true,
             state: InstancedComponentState::NotLoaded,
-        };
-
-        Ok(component)
+        }
     }
 
     // add a new Component Data
     // this will create a new instance within the object
     // of the InstancedShader
-    pub fn add(&mut self, object_data: &ObjectData) {
+    // the handle stays valid until the instance is removed
+    pub fn add(&mut self, object_data: &ObjectData) -> InstanceHandle {
         self.object_data.push(object_data.clone());
         self.object.add(object_data);
-        self.object.set_state(ObjectState::Reload);
+        self.added();
+        self.handles.insert()
+    }
+
+    // add many Component Data at once,
+    // returns the handles in the same order
+    pub fn extend<I>(&mut self, object_data: I) -> Vec<InstanceHandle>
+    where
+        I: IntoIterator<Item = ObjectData>,
+    {
+        let mut handles = vec![];
+        for object_data in object_data {
+            self.object.add(&object_data);
+            self.object_data.push(object_data);
+            handles.push(self.handles.insert());
+        }
+
+        self.added();
+        handles
+    }
+
+    // change the Component Data of all instances in a closure,
+    // the object gets updated once afterwards
+    pub fn edit<F>(&mut self, edit: F)
+    where
+        F: FnOnce(&mut [ObjectData]),
+    {
+        edit(&mut self.object_data);
+        self.object.set_all(&self.object_data);
+        self.reordered();
+    }
+
+    // get the Component Data of all instances
+    pub fn instances(&self) -> &[ObjectData] {
+        &self.object_data
+    }
+
+    // the number of instances
+    pub fn len(&self) -> usize {
+        self.object_data.len()
+    }
+
+    // true if there are no instances
+    pub fn is_empty(&self) -> bool {
+        self.object_data.is_empty()
+    }
+
+    // add a new polyline to a line InstancedShader
+    // the color of the object data is the color of the first segment
+    pub fn add_polyline(&mut self, polyline: &Polyline) -> Result<InstanceHandle, String> {
+        self.object.add_polyline(polyline)?;
+
+        let mut object_data = ObjectData::default();
+        if let Some(color) = polyline.colors.first() {
+            object_data.color = *color;
+        }
+
+        self.object_data.push(object_data);
+        self.added();
+        Ok(self.handles.insert())
+    }
+
+    // replace the polyline of instance i of a line InstancedShader
+    pub fn set_polyline(&mut self, i: impl InstanceRef, polyline: &Polyline) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object.set_polyline(i, polyline)?;
+        self.changed();
+        Ok(())
+    }
+
+    // add a new polygon to a polygon InstancedShader
+    // the color of the object data is the color of the first point
+    pub fn add_polygon(&mut self, polygon: &PolygonData) -> Result<InstanceHandle, String> {
+        self.object.add_polygon(polygon)?;
+
+        let mut object_data = ObjectData::default();
+        if let Some(color) = polygon.colors().first() {
+            object_data.color = *color;
+        }
+
+        self.object_data.push(object_data);
+        self.added();
+        Ok(self.handles.insert())
+    }
+
+    // replace the polygon of instance i of a polygon InstancedShader
+    pub fn set_polygon(&mut self, i: impl InstanceRef, polygon: &PolygonData) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object.set_polygon(i, polygon)?;
+        self.changed();
+        Ok(())
     }
 
     // remove a Component Data
@@ -105,7 +245,49 @@ impl InstancedShader {
         self.index_oob(i)?;
         self.object_data.remove(i);
         self.object.remove(i);
-        self.object.set_state(ObjectState::Reload);
+        self.handles.remove(i);
+        self.reordered();
+        Ok(())
+    }
+
+    // remove the instance of a handle,
+    // the last instance moves into its place
+    pub fn remove_handle(&mut self, handle: InstanceHandle) -> Result<(), String> {
+        let i = self.handles.swap_remove(handle)?;
+        self.object_data.swap_remove(i);
+        self.object.swap_remove(i);
+        self.reordered();
+        Ok(())
+    }
+
+    // get the index of the instance of a handle
+    pub fn index(&self, handle: InstanceHandle) -> Result<usize, String> {
+        self.handles.index(handle)
+    }
+
+    // get the handle of instance i
+    pub fn handle(&self, i: usize) -> Result<InstanceHandle, String> {
+        self.index_oob(i)?;
+        self.handles.handle(i).ok_or(format!("Component Data with index '{}' has no handle.", i))
+    }
+
+    // true if the instance of the handle was not removed
+    pub fn contains(&self, handle: InstanceHandle) -> bool {
+        self.handles.index(handle).is_ok()
+    }
+
+    // get the object data of the instance of a handle
+    pub fn get(&self, handle: InstanceHandle) -> Result<ObjectData, String> {
+        let i = self.handles.index(handle)?;
+        Ok(self.object_data[i])
+    }
+
+    // replace the object data of the instance of a handle
+    pub fn set(&mut self, handle: InstanceHandle, object_data: &ObjectData) -> Result<(), String> {
+        let i = self.handles.index(handle)?;
+        self.object_data[i] = *object_data;
+        self.object.set(i, object_data);
+        self.moved(i);
         Ok(())
     }
 
@@ -115,7 +297,8 @@ impl InstancedShader {
     pub fn remove_all(&mut self) {
         self.object_data = vec![];
         self.object.remove_all();
-        self.object.set_state(ObjectState::Reload);
+        self.handles.clear();
+        self.reordered();
     }
 
     // loads the object with the model data
@@ -133,83 +316,141 @@ impl InstancedShader {
             _ => (),
         }
 
-        self.object.draw(draw, camera, &self.transform)?;
-        Ok(())
+        // only send the instances that are on the screen
+        if self.culling {
+            let visible = self.visible(draw, camera);
+            self.object.set_visible(Some(visible));
+        }
+
+        // use the blend mode only for this InstancedShader
+        let previous = self.blend.apply();
+        let result = self.object.draw(draw, camera, &self.transform);
+        previous.apply();
+        result
+    }
+
+    // skip the instances that are outside of the screen when drawing,
+    // lines and polygons are always drawn completely
+    pub fn set_culling(&mut self, culling: bool) {
+        self.culling = culling;
+        if !culling {
+            self.object.set_visible(None);
+            // don't keep the grid up to date while it is not used
+            self.grid_dirty = true;
+        }
+    }
+
+    // true if instances outside of the screen are skipped
+    pub fn culling(&self) -> bool {
+        self.culling
+    }
+
+    // set the size of the cells of the grid
+    // that finds the instances on the screen
+    pub fn set_culling_cell_size(&mut self, cell_size: f32) {
+        self.grid = SpatialGrid::new(cell_size);
+        self.grid_dirty = true;
+    }
+
+    // set how all instances are combined with what is already drawn
+    pub fn set_blend(&mut self, blend: BlendMode) {
+        self.blend = blend;
+    }
+
+    // get how all instances are combined with what is already drawn
+    pub fn blend(&self) -> BlendMode {
+        self.blend
+    }
+
+    // replace the image that all instances of a texture InstancedShader show
+    pub fn set_image(&mut self, image: &Image) -> Result<(), String> {
+        self.object.set_image(image)
+    }
+
+    // replace a part of the image of a texture InstancedShader,
+    // x and y are from the top left corner of the image
+    pub fn set_sub_image(&mut self, x: u32, y: u32, image: &Image) -> Result<(), String> {
+        self.object.set_sub_image(x, y, image)
+    }
+
+    // change how the texture of a texture InstancedShader is sampled
+    pub fn set_texture_options(&mut self, options: &TextureOptions) -> Result<(), String> {
+        self.object.set_texture_options(options)
     }
 
     // set the width and the height of a transform data i of the InstancedShader
-    pub fn set_dim(&mut self, i: usize, width: f32, height: f32) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_dim(&mut self, i: impl InstanceRef, width: f32, height: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].dim.0 = width;
         self.object_data[i].dim.1 = height;
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.moved(i);
         Ok(())
     }
 
     // get the width and the height of a transform data i the InstancedShader
-    pub fn dim(&self, i: usize) -> Result<(f32, f32), String> {
-        self.index_oob(i)?;
+    pub fn dim(&self, i: impl InstanceRef) -> Result<(f32, f32), String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].dim)
     }
 
     // set the width of a transform data i of the InstancedShader
-    pub fn set_width(&mut self, i: usize, width: f32) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_width(&mut self, i: impl InstanceRef, width: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].dim.0 = width;
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.moved(i);
         Ok(())
     }
 
     // get the width of a transform data i of the InstancedShader
-    pub fn width(&self, i: usize) -> Result<f32, String> {
-        self.index_oob(i)?;
+    pub fn width(&self, i: impl InstanceRef) -> Result<f32, String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].dim.0)
     }
 
     // set the height of a transform data i of the InstancedShader
-    pub fn set_height(&mut self, i: usize, height: f32) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_height(&mut self, i: impl InstanceRef, height: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].dim.1 = height;
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.moved(i);
         Ok(())
     }
 
     // get the height of a transform data i of the InstancedShader
-    pub fn height(&self, i: usize) -> Result<f32, String> {
-        self.index_oob(i)?;
+    pub fn height(&self, i: impl InstanceRef) -> Result<f32, String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].dim.1)
     }
 
     // set the color of a transform data i of the InstancedShader
-    pub fn set_color(&mut self, i: usize, color: &Color) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_color(&mut self, i: impl InstanceRef, color: &Color) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].color = color.clone();
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.changed();
         Ok(())
     }
 
     // get the color of a transform data i of the InstancedShader
-    pub fn color(&self, i: usize) -> Result<Color, String> {
-        self.index_oob(i)?;
+    pub fn color(&self, i: impl InstanceRef) -> Result<Color, String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].color)
     }
 
     // set the opacity of a transform data i of the InstancedShader
-    pub fn set_opacity(&mut self, i: usize, opacity: f32) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_opacity(&mut self, i: impl InstanceRef, opacity: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].opacity = opacity;
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.changed();
         Ok(())
     }
 
     // get the opacity of a transform data i of the InstancedShader
-    pub fn opacity(&self, i: usize) -> Result<f32, String> {
-        self.index_oob(i)?;
+    pub fn opacity(&self, i: impl InstanceRef) -> Result<f32, String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].opacity)
     }
 
@@ -217,41 +458,207 @@ impl InstancedShader {
     // the offset os mainly used for
     // better positioning of rotation
     // or when using instanced drawing
-    pub fn set_offset(&mut self, i: usize, x_offset: f32, y_offset: f32) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_offset(&mut self, i: impl InstanceRef, x_offset: f32, y_offset: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].offset = (x_offset, y_offset);
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.moved(i);
         Ok(())
     }
 
     // get the offset of transform data i of the InstancedShader
-    pub fn offset(&self, i: usize) -> Result<(f32, f32), String> {
-        self.index_oob(i)?;
+    pub fn offset(&self, i: impl InstanceRef) -> Result<(f32, f32), String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].offset)
     }
 
     // set the texture coordinate of transform data i of the InstancedShader
-    pub fn set_texcoord(&mut self, i: usize, texcoord: TextureCoordinate) -> Result<(), String> {
-        self.index_oob(i)?;
+    pub fn set_texcoord(&mut self, i: impl InstanceRef, texcoord: TextureCoordinate) -> Result<(), String> {
+        let i = i.instance_index(self)?;
         self.object_data[i].texcoord = texcoord;
         self.object.set(i, &self.object_data[i]);
-        self.object.set_state(ObjectState::Reload);
+        self.changed();
         Ok(())
     }
 
     // get the texture coordinate of transform data i of the InstancedShader
-    pub fn texcoord(&self, i: usize) -> Result<TextureCoordinate, String> {
-        self.index_oob(i)?;
+    pub fn texcoord(&self, i: impl InstanceRef) -> Result<TextureCoordinate, String> {
+        let i = i.instance_index(self)?;
         Ok(self.object_data[i].texcoord)
     }
 
+    // set the corner radius of transform data i of a rect InstancedShader
+    pub fn set_corner_radius(&mut self, i: impl InstanceRef, corner_radius: [f32; 4]) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].corner_radius = corner_radius;
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the corner radius of transform data i of the InstancedShader
+    pub fn corner_radius(&self, i: impl InstanceRef) -> Result<[f32; 4], String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].corner_radius)
+    }
+
+    // set the width and color of the outline of transform data i of the InstancedShader
+    pub fn set_stroke(&mut self, i: impl InstanceRef, width: f32, color: &Color) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].stroke_width = width;
+        self.object_data[i].stroke_color = *color;
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the width and color of the outline of transform data i of the InstancedShader
+    pub fn stroke(&self, i: impl InstanceRef) -> Result<(f32, Color), String> {
+        let i = i.instance_index(self)?;
+        Ok((self.object_data[i].stroke_width, self.object_data[i].stroke_color))
+    }
+
+    // set the inner radius of transform data i of a circle InstancedShader
+    pub fn set_inner_radius(&mut self, i: impl InstanceRef, inner_radius: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].inner_radius = inner_radius;
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the inner radius of transform data i of the InstancedShader
+    pub fn inner_radius(&self, i: impl InstanceRef) -> Result<f32, String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].inner_radius)
+    }
+
+    // set the start and end angle of transform data i of a circle InstancedShader
+    pub fn set_arc(&mut self, i: impl InstanceRef, start: f32, end: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].arc = (start, end);
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the start and end angle of transform data i of the InstancedShader
+    pub fn arc(&self, i: impl InstanceRef) -> Result<(f32, f32), String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].arc)
+    }
+
+    // set the drop shadow of transform data i of the InstancedShader
+    pub fn set_shadow(&mut self, i: impl InstanceRef, shadow: &Shadow) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].shadow = *shadow;
+        self.object.set(i, &self.object_data[i]);
+        self.moved(i);
+        Ok(())
+    }
+
+    // get the drop shadow of transform data i of the InstancedShader
+    pub fn shadow(&self, i: impl InstanceRef) -> Result<Shadow, String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].shadow)
+    }
+
+    // set the gradient fill of transform data i of the InstancedShader
+    pub fn set_gradient(&mut self, i: impl InstanceRef, gradient: &Gradient) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].gradient = *gradient;
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the gradient fill of transform data i of the InstancedShader
+    pub fn gradient(&self, i: impl InstanceRef) -> Result<Gradient, String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].gradient)
+    }
+
+    // set the rotation of transform data i of the InstancedShader
+    // in radians, clockwise around its pivot
+    pub fn set_rotation(&mut self, i: impl InstanceRef, rotation: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].rotation = rotation;
+        self.object.set(i, &self.object_data[i]);
+        self.moved(i);
+        Ok(())
+    }
+
+    // get the rotation of transform data i of the InstancedShader in radians
+    pub fn rotation(&self, i: impl InstanceRef) -> Result<f32, String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].rotation)
+    }
+
+    // set the point transform data i of the InstancedShader
+    // rotates around as a fraction of its dimension
+    pub fn set_pivot(&mut self, i: impl InstanceRef, x: f32, y: f32) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].pivot = (x, y);
+        self.object.set(i, &self.object_data[i]);
+        self.moved(i);
+        Ok(())
+    }
+
+    // get the point transform data i of the InstancedShader rotates around
+    pub fn pivot(&self, i: impl InstanceRef) -> Result<(f32, f32), String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].pivot)
+    }
+
+    // mirror transform data i of the InstancedShader
+    // horizontally and/or vertically
+    pub fn set_flip(&mut self, i: impl InstanceRef, flip_x: bool, flip_y: bool) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].flip_x = flip_x;
+        self.object_data[i].flip_y = flip_y;
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the horizontal and vertical mirroring
+    // of transform data i of the InstancedShader
+    pub fn flip(&self, i: impl InstanceRef) -> Result<(bool, bool), String> {
+        let i = i.instance_index(self)?;
+        Ok((self.object_data[i].flip_x, self.object_data[i].flip_y))
+    }
+
+    // mix the color into transform data i
+    // of a texture InstancedShader with a mode
+    pub fn set_tint(&mut self, i: impl InstanceRef, color: &Color, mode: TintMode) -> Result<(), String> {
+        let i = i.instance_index(self)?;
+        self.object_data[i].color = *color;
+        self.object_data[i].tint = mode;
+        self.object.set(i, &self.object_data[i]);
+        self.changed();
+        Ok(())
+    }
+
+    // get the mode that mixes the color into transform data i
+    // of a texture InstancedShader
+    pub fn tint(&self, i: impl InstanceRef) -> Result<TintMode, String> {
+        let i = i.instance_index(self)?;
+        Ok(self.object_data[i].tint)
+    }
+
     // collision for an instance
-    pub fn instance_collides(&self, i: usize, x: f32, y: f32) -> Result<bool, String> {
+    pub fn instance_collides(&self, i: impl InstanceRef, x: f32, y: f32) -> Result<bool, String> {
+        let i = i.instance_index(self)?;
         let (tx, ty, _) = self.transform.pos();
         let (x_offset, y_offset) = self.offset(i)?;
         let (width, height) = self.dim(i)?;
-        let collides = collision::point_in_rect(x, y, tx + x_offset, ty + y_offset, width, height);
+        let (pivot_x, pivot_y) = self.object_data[i].pivot_position();
+
+        // rotate the point back so the instance is straight again
+        let (rect_x, rect_y) = (tx + x_offset, ty + y_offset);
+        let rotation = self.object_data[i].rotation;
+        let (x, y) = collision::rotate_point(x, y, rect_x + pivot_x, rect_y + pivot_y, -rotation);
+        let collides = collision::point_in_rect(x, y, rect_x, rect_y, width, height);
         Ok(collides)
     }
 
@@ -268,9 +675,57 @@ impl InstancedShader {
         Ok(None)
     }
 
+    // the instances changed and need to be
+    // sent to the graphics card again
+    fn changed(&mut self) {
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // instance i moved or changed its size,
+    // only it gets moved in the grid
+    fn moved(&mut self, i: usize) {
+        self.changed();
+        if !self.grid_dirty {
+            self.grid.update(i, culling::instance_bounds(&self.object_data[i]));
+        }
+    }
+
+    // instances were added at the end
+    // and get added to the grid
+    fn added(&mut self) {
+        self.changed();
+        if !self.grid_dirty {
+            for object_data in &self.object_data[self.grid.len()..] {
+                self.grid.insert(culling::instance_bounds(object_data));
+            }
+        }
+    }
+
+    // instances were removed or all changed,
+    // the grid gets rebuilt on the next draw
+    fn reordered(&mut self) {
+        self.changed();
+        self.grid_dirty = true;
+    }
+
+    // the indices of the instances that are on the screen,
+    // the grid only gets rebuilt after the instances changed
+    fn visible(&mut self, draw: &Draw, camera: &Transform) -> Vec<usize> {
+        if self.grid_dirty {
+            self.grid.clear();
+            for object_data in &self.object_data {
+                self.grid.insert(culling::instance_bounds(object_data));
+            }
+            self.grid_dirty = false;
+        }
+
+        let area = culling::visible_area(&draw.window, camera, &self.transform);
+        self.grid.query(area)
+    }
+
     // checks if a item is in the
     // InstancedShader data vector
-    fn index_oob(&self, i: usize) -> Result<(), String> {
+    pub(crate) fn index_oob(&self, i: usize) -> Result<(), String> {
         if i >= self.object_data.len() {
             Err(format!("Component Data with index '{}' not found.", i))
         } else {
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use crate::core::math::transform::Transform;
+use crate::core::project::Draw;
+use crate::core::shader::glyphs::GlyphText;
+use crate::core::shader::instanced::InstancedShader;
+use crate::core::shader::mesh::Mesh;
+use crate::core::shader::shader::Shader;
+
+// Implement this Trait on everything that can be
+// drawn in the order of a RenderQueue
+pub trait Drawable {
+    fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String>;
+
+    // the y value that layers with y sorting use,
+    // usually where the object touches the ground
+    fn sort_y(&self) -> f32;
+}
+
+impl Drawable for Shader {
+    fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        Shader::draw(self, draw, camera)
+    }
+
+    // the bottom edge of the Shader
+    fn sort_y(&self) -> f32 {
+        let (_, y, _) = self.transform.pos();
+        y + self.offset().1 + self.height()
+    }
+}
+
+impl Drawable for GlyphText {
+    fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        GlyphText::draw(self, draw, camera)
+    }
+
+    // the bottom edge of the GlyphText
+    fn sort_y(&self) -> f32 {
+        let (_, y, _) = self.transform.pos();
+        y + self.height()
+    }
+}
+
+impl Drawable for Mesh {
+    fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        Mesh::draw(self, draw, camera)
+    }
+
+    // the position of the Mesh
+    fn sort_y(&self) -> f32 {
+        let (_, y, _) = self.transform.pos();
+        y
+    }
+}
+
+impl Drawable for InstancedShader {
+    fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        InstancedShader::draw(self, draw, camera)
+    }
+
+    // the position of the InstancedShader
+    fn sort_y(&self) -> f32 {
+        let (_, y, _) = self.transform.pos();
+        y
+    }
+}
+
+// the settings of a single layer
+#[derive(Clone, Copy, Default)]
+pub struct Layer {
+    // a camera that replaces the camera of the queue,
+    // e.g. the identity transform for a UI layer
+    pub camera: Option<Transform>,
+    // draw objects that are further down later
+    pub y_sort: bool,
+}
+
+/// the settings of all layers, layers are drawn from the lowest
+/// to the highest number, layers without settings use the default
+/// ```rust
+/// let mut layers = Layers::new();
+/// layers.set(0, Layer { y_sort: true, ..Layer::default() });
+/// layers.set(10, Layer { camera: Some(Transform::new()), ..Layer::default() });
+///
+/// let mut queue = layers.queue();
+/// queue.submit(0, 0.0, &mut self.player);
+/// queue.submit(10, 0.0, &mut self.health_bar);
+/// queue.draw(&draw, &self.camera)?;
+/// ```
+#[derive(Default)]
+pub struct Layers {
+    layers: BTreeMap<i32, Layer>,
+}
+
+impl Layers {
+    // no layers with settings
+    pub fn new() -> Self {
+        Self {
+            layers: BTreeMap::new(),
+        }
+    }
+
+    // set the settings of a layer
+    pub fn set(&mut self, layer: i32, settings: Layer) {
+        self.layers.insert(layer, settings);
+    }
+
+    // get the settings of a layer
+    pub fn get(&self, layer: i32) -> Layer {
+        self.layers.get(&layer).copied().unwrap_or_default()
+    }
+
+    // remove the settings of a layer
+    pub fn remove(&mut self, layer: i32) {
+        self.layers.remove(&layer);
+    }
+
+    // create an empty queue for a frame
+    pub fn queue(&self) -> RenderQueue<'_> {
+        RenderQueue {
+            layers: self,
+            items: vec![],
+        }
+    }
+}
+
+// an object in the RenderQueue
+struct Item<'a> {
+    layer: i32,
+    z: f32,
+    drawable: &'a mut dyn Drawable,
+}
+
+/*
+a RenderQueue collects the objects of a frame
+and draws them sorted by layer, z and optionally y
+
+objects with the same position in the order
+are drawn in the order they were submitted
+*/
+pub struct RenderQueue<'a> {
+    layers: &'a Layers,
+    items: Vec<Item<'a>>,
+}
+
+impl<'a> RenderQueue<'a> {
+    // add an object to a layer, a higher z is drawn later
+    pub fn submit(&mut self, layer: i32, z: f32, drawable: &'a mut dyn Drawable) {
+        self.items.push(Item { layer, z, drawable });
+    }
+
+    // the number of objects in the queue
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    // true if there are no objects in the queue
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // draw all objects in order and empty the queue,
+    // the camera is used by all layers without their own camera
+    pub fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        let keys: Vec<(i32, f32, f32)> = self
+            .items
+            .iter()
+            .map(|item| {
+                let y = if self.layers.get(item.layer).y_sort { item.drawable.sort_y() } else { 0.0 };
+                (item.layer, item.z, y)
+            })
+            .collect();
+
+        let mut items: Vec<Option<Item>> = self.items.drain(..).map(Some).collect();
+        for i in sort_order(&keys) {
+            if let Some(item) = items[i].take() {
+                let layer_camera = self.layers.get(item.layer).camera;
+                item.drawable.draw(draw, layer_camera.as_ref().unwrap_or(camera))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// the order of items with a layer, z and y,
+// sorted stable so equal items keep their order
+fn sort_order(keys: &[(i32, f32, f32)]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by(|a, b| {
+        let (a, b) = (keys[*a], keys[*b]);
+        a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.total_cmp(&b.2))
+    });
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_order() {
+        let keys = [(1, 0.0, 0.0), (0, 5.0, 0.0), (0, 1.0, 0.0), (0, 1.0, 0.0), (0, 1.0, -3.0)];
+        assert_eq!(vec![4, 2, 3, 1, 0], sort_order(&keys));
+        assert!(sort_order(&[]).is_empty());
+    }
+}
@@ -0,0 +1,656 @@
+use crate::core::color::Color;
+use crate::core::math::mvp;
+use crate::core::math::pathfinding::Maze;
+use crate::core::math::transform::Transform;
+use crate::core::shader::blend::BlendState;
+use crate::core::shader::clip::{ScissorState, StencilState};
+use crate::core::shader::object::{Buffer, VertexArray};
+use crate::core::shader::program::Program;
+use crate::core::shader::target::RenderTarget;
+use crate::core::shader::Shader;
+use crate::core::window::Window;
+use crate::Draw;
+
+// lights and shadows are drawn in the units of the world
+const LIGHT_VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in vec2 position;
+
+    uniform mat4 projection;
+    uniform mat4 view;
+
+    out vec2 oPosition;
+
+    void main() {
+        gl_Position = projection * view * vec4(position, 0.0, 1.0);
+        oPosition = position;
+    }
+"#;
+
+const LIGHT_FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform vec2 center;
+    uniform vec4 light;
+    uniform float radius;
+    uniform float falloff;
+    uniform vec4 spot;
+
+    in vec2 oPosition;
+
+    out vec4 color;
+
+    void main() {
+        vec2 to_pixel = oPosition - center;
+        float dist = length(to_pixel);
+        float attenuation = pow(clamp(1.0 - dist / radius, 0.0, 1.0), falloff);
+
+        // a spot light only shines into its cone,
+        // the last fifth of the cone fades out
+        if(spot.w > 0.5 && dist > 0.0) {
+            float angle = acos(clamp(dot(to_pixel / dist, spot.xy), -1.0, 1.0));
+            attenuation *= 1.0 - smoothstep(spot.z * 0.8, spot.z, angle);
+        }
+
+        color = vec4(light.rgb * light.a * attenuation, 1.0);
+    }
+"#;
+
+// the light map is put on the scene as a fullscreen rectangle
+const MAP_VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in vec2 position;
+
+    out vec2 oTexCoord;
+
+    void main() {
+        gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+        oTexCoord = position;
+    }
+"#;
+
+const MAP_FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        color = texture(sampler, oTexCoord);
+    }
+"#;
+
+// the number of positions a soft light is drawn from
+const SOFT_SAMPLES: usize = 8;
+
+// a line from point to point that blocks light
+type Edge = ((f32, f32), (f32, f32));
+
+/// the shape of the light
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LightKind {
+    // shines in every direction
+    Point,
+    // shines into a cone, direction and angle in radians,
+    // angle is half of the width of the cone
+    Spot { direction: f32, angle: f32 },
+}
+
+/// A light that is added to the ambient color of the Lighting
+/// ```rust
+/// let mut torch = Light::point(200.0, 150.0, 300.0, &Color::rgb(255, 200, 140));
+/// torch.softness = 12.0;
+///
+/// let flashlight = Light::spot(400.0, 300.0, 500.0, 0.0, 0.4, &Color::grey(255));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Light {
+    // the center of the light in the world
+    pub position: (f32, f32),
+    pub color: Color,
+    // the distance the light reaches
+    pub radius: f32,
+    // how fast the light gets darker, 1.0 is linear, 2.0 is quadratic
+    pub falloff: f32,
+    pub intensity: f32,
+    pub kind: LightKind,
+    // the size of the light source, 0.0 casts hard shadows
+    pub softness: f32,
+    // false lets the light shine through occluders
+    pub shadows: bool,
+}
+
+impl Light {
+    // a light that shines in every direction
+    pub fn point(x: f32, y: f32, radius: f32, color: &Color) -> Self {
+        Self {
+            position: (x, y),
+            color: *color,
+            radius,
+            falloff: 1.0,
+            intensity: 1.0,
+            kind: LightKind::Point,
+            softness: 0.0,
+            shadows: true,
+        }
+    }
+
+    // a light that shines into a cone,
+    // direction and angle in radians
+    pub fn spot(x: f32, y: f32, radius: f32, direction: f32, angle: f32, color: &Color) -> Self {
+        Self {
+            kind: LightKind::Spot { direction, angle },
+            ..Self::point(x, y, radius, color)
+        }
+    }
+}
+
+/// A light map of an ambient color, lights and the shadows
+/// of occluders that darkens everything drawn before it
+///
+/// create the lighting in load
+/// ```rust
+/// let mut lighting = Lighting::new(&Window::new(1270.0, 700.0)).unwrap();
+/// lighting.set_ambient(&Color::rgb(20, 20, 40));
+/// let torch = lighting.add_light(Light::point(200.0, 150.0, 300.0, &Color::rgb(255, 200, 140)));
+/// lighting.add_occluder(&[(300.0, 100.0), (340.0, 100.0), (340.0, 200.0), (300.0, 200.0)]);
+/// lighting.add_maze(&level, 32.0, (0.0, 0.0));
+/// ```
+///
+/// draw the scene and then the lighting in update
+/// ```rust
+/// lighting.light_mut(torch).unwrap().position = player.position();
+/// level.draw(&draw, &camera).unwrap();
+/// lighting.draw(&draw, &camera).unwrap();
+/// ```
+///
+/// the light map goes onto whatever is drawn to,
+/// so lit scenes can go through a PostProcess
+/// ```rust
+/// post.begin(&draw).unwrap();
+/// level.draw(&draw, &camera).unwrap();
+/// lighting.draw(&draw, &camera).unwrap();
+/// post.end(&draw).unwrap();
+/// ```
+pub struct Lighting {
+    ambient: Color,
+    lights: Vec<Light>,
+    occluders: Vec<Vec<Edge>>,
+    light_map: RenderTarget,
+    light_program: Program,
+    map_program: Program,
+    world_array: VertexArray,
+    world_buffer: Buffer,
+    map_array: VertexArray,
+    map_buffer: Buffer, // the buffer needs to stay alive
+}
+
+impl Lighting {
+    // create a Lighting without lights that has the size of the window
+    pub fn new(window: &Window) -> Result<Self, String> {
+        let model_data: [f32; 4 * 2] = [
+            1.0, 0.0, // bottom right 0
+            0.0, 0.0, // bottom left 1
+            0.0, 1.0, // top left 2
+            1.0, 1.0, // top right 3
+        ];
+
+        let lighting = Self {
+            ambient: Color::grey(0),
+            lights: vec![],
+            occluders: vec![],
+            light_map: RenderTarget::window(window)?,
+            light_program: Self::program(LIGHT_VERTEX_SHADER_SOURCE, LIGHT_FRAGMENT_SHADER_SOURCE)?,
+            map_program: Self::program(MAP_VERTEX_SHADER_SOURCE, MAP_FRAGMENT_SHADER_SOURCE)?,
+            world_array: VertexArray::new(),
+            world_buffer: Buffer::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW),
+            map_array: VertexArray::new(),
+            map_buffer: Buffer::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW),
+        };
+
+        // the lights and shadows get new vertices every draw
+        lighting.world_array.bind();
+        lighting.world_buffer.bind();
+
+        unsafe {
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 8, std::ptr::null()); // position
+            gl::EnableVertexAttribArray(0);
+        }
+
+        // create the buffer for the fullscreen rectangle
+        lighting.map_array.bind();
+        lighting.map_buffer.set_data(&model_data.to_vec());
+
+        unsafe {
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 8, std::ptr::null()); // position
+            gl::EnableVertexAttribArray(0);
+        }
+
+        Ok(lighting)
+    }
+
+    // set the color of places no light reaches
+    pub fn set_ambient(&mut self, color: &Color) {
+        self.ambient = *color;
+    }
+
+    // get the color of places no light reaches
+    pub fn ambient(&self) -> Color {
+        self.ambient
+    }
+
+    // add a light and get its index
+    pub fn add_light(&mut self, light: Light) -> usize {
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    // replace light i
+    pub fn set_light(&mut self, i: usize, light: Light) -> Result<(), String> {
+        match self.lights.get_mut(i) {
+            Some(old) => {
+                *old = light;
+                Ok(())
+            }
+            None => Err(format!("Light with index '{}' not found.", i)),
+        }
+    }
+
+    // get light i to change it
+    pub fn light_mut(&mut self, i: usize) -> Option<&mut Light> {
+        self.lights.get_mut(i)
+    }
+
+    // remove light i, the lights after it move down by one
+    pub fn remove_light(&mut self, i: usize) -> Result<(), String> {
+        if i >= self.lights.len() {
+            return Err(format!("Light with index '{}' not found.", i));
+        }
+
+        self.lights.remove(i);
+        Ok(())
+    }
+
+    // remove all lights
+    pub fn clear_lights(&mut self) {
+        self.lights = vec![];
+    }
+
+    // the number of lights
+    pub fn light_count(&self) -> usize {
+        self.lights.len()
+    }
+
+    // add a polygon in the world that casts shadows
+    // and get its index, the polygon gets closed
+    pub fn add_occluder(&mut self, points: &[(f32, f32)]) -> usize {
+        self.occluders.push(polygon_edges(points));
+        self.occluders.len() - 1
+    }
+
+    // add the solid tiles of a maze as one occluder and get its index,
+    // the tiles are tile_size big and the maze starts at origin
+    pub fn add_maze(&mut self, maze: &dyn Maze, tile_size: f32, origin: (f32, f32)) -> usize {
+        self.occluders.push(maze_edges(maze, tile_size, origin));
+        self.occluders.len() - 1
+    }
+
+    // remove occluder i, the occluders after it move down by one
+    pub fn remove_occluder(&mut self, i: usize) -> Result<(), String> {
+        if i >= self.occluders.len() {
+            return Err(format!("Occluder with index '{}' not found.", i));
+        }
+
+        self.occluders.remove(i);
+        Ok(())
+    }
+
+    // remove all occluders
+    pub fn clear_occluders(&mut self) {
+        self.occluders = vec![];
+    }
+
+    /*
+    draw the lights into the light map and multiply
+    everything drawn before with it, the camera has
+    to be the camera the scene was drawn with
+    */
+    pub fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        let blend = BlendState::current();
+        let scissor = ScissorState::current();
+        let stencil = StencilState::current();
+
+        self.light_map.bind(draw)?;
+        self.light_map.clear(&self.ambient);
+
+        unsafe {
+            // clips of the window don't fit the light map
+            gl::Disable(gl::SCISSOR_TEST);
+            // lights add up
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+        }
+
+        let result = self.draw_lights(draw, camera);
+
+        unsafe {
+            gl::Disable(gl::STENCIL_TEST);
+        }
+        stencil.restore();
+        scissor.restore();
+        self.light_map.unbind(draw);
+
+        if result.is_ok() {
+            self.draw_map();
+        }
+        blend.restore();
+        result
+    }
+
+    // draw every light into the bound light map
+    fn draw_lights(&self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        let projection = mvp::ortho(&draw.window);
+        let view = camera.matrix();
+
+        unsafe {
+            self.light_program.bind();
+            self.world_array.bind();
+
+            let projection_location = self.light_program.get_uniform_location("projection")?;
+            let view_location = self.light_program.get_uniform_location("view")?;
+            gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
+            gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
+        }
+
+        for light in &self.lights {
+            let (x, y) = light.position;
+
+            // the edges this light can reach
+            let reach = light.radius + light.softness;
+            let edges: Vec<Edge> = if light.shadows {
+                self.occluders
+                    .iter()
+                    .flatten()
+                    .filter(|edge| segment_distance((x, y), **edge) < reach)
+                    .copied()
+                    .collect()
+            } else {
+                vec![]
+            };
+
+            let samples = if edges.is_empty() { vec![(x, y)] } else { light_samples(light) };
+            let spot = match light.kind {
+                LightKind::Point => [0.0; 4],
+                LightKind::Spot { direction, angle } => [direction.cos(), direction.sin(), angle, 1.0],
+            };
+            let color = light.color;
+            let intensity = light.intensity / samples.len() as f32;
+
+            unsafe {
+                let center_location = self.light_program.get_uniform_location("center")?;
+                let light_location = self.light_program.get_uniform_location("light")?;
+                let radius_location = self.light_program.get_uniform_location("radius")?;
+                let falloff_location = self.light_program.get_uniform_location("falloff")?;
+                let spot_location = self.light_program.get_uniform_location("spot")?;
+                gl::Uniform2f(center_location, x, y);
+                gl::Uniform4f(light_location, color.r, color.g, color.b, intensity);
+                gl::Uniform1f(radius_location, light.radius);
+                gl::Uniform1f(falloff_location, light.falloff);
+                gl::Uniform4fv(spot_location, 1, spot.as_ptr());
+            }
+
+            for sample in samples {
+                unsafe {
+                    if edges.is_empty() {
+                        gl::Disable(gl::STENCIL_TEST);
+                    } else {
+                        // mark the shadows in the stencil buffer
+                        gl::Enable(gl::STENCIL_TEST);
+                        gl::Clear(gl::STENCIL_BUFFER_BIT);
+                        gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+                        gl::StencilFunc(gl::ALWAYS, 1, 0xFF);
+                        gl::StencilOp(gl::KEEP, gl::KEEP, gl::REPLACE);
+                        self.draw_triangles(shadow_triangles(sample, reach, &edges));
+
+                        // and only light the rest
+                        gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+                        gl::StencilFunc(gl::EQUAL, 0, 0xFF);
+                        gl::StencilOp(gl::KEEP, gl::KEEP, gl::KEEP);
+                    }
+                }
+
+                self.draw_triangles(light_quad(light.position, light.radius));
+            }
+        }
+
+        Ok(())
+    }
+
+    // draw triangles in the world with the bound program
+    fn draw_triangles(&self, vertices: Vec<f32>) {
+        self.world_buffer.set_data(&vertices);
+
+        unsafe {
+            gl::DrawArrays(gl::TRIANGLES, 0, (vertices.len() / 2) as i32);
+        }
+    }
+
+    // multiply the window with the light map
+    fn draw_map(&self) {
+        unsafe {
+            // the alpha of the window stays the same
+            gl::Enable(gl::BLEND);
+            gl::BlendFuncSeparate(gl::DST_COLOR, gl::ZERO, gl::ZERO, gl::ONE);
+
+            self.map_program.bind();
+            self.map_array.bind();
+            self.light_map.texture_buffer().bind();
+            gl::DrawArrays(gl::TRIANGLE_FAN, 0, 4);
+        }
+    }
+
+    // compile a shader program
+    fn program(vertex_source: &str, fragment_source: &str) -> Result<Program, String> {
+        let vertex_shader = Shader::new(vertex_source, gl::VERTEX_SHADER)?;
+        let fragment_shader = Shader::new(fragment_source, gl::FRAGMENT_SHADER)?;
+        Program::new(&vertex_shader, &fragment_shader)
+    }
+}
+
+// the edges of a closed polygon
+fn polygon_edges(points: &[(f32, f32)]) -> Vec<Edge> {
+    if points.len() < 2 {
+        return vec![];
+    }
+
+    (0..points.len())
+        .map(|i| (points[i], points[(i + 1) % points.len()]))
+        .collect()
+}
+
+// the outline of the solid tiles of a maze,
+// sides between two solid tiles block nothing
+fn maze_edges(maze: &dyn Maze, tile_size: f32, origin: (f32, f32)) -> Vec<Edge> {
+    let solid = |x: i64, y: i64| {
+        x >= 0 && y >= 0 && (x as usize) < maze.width() && (y as usize) < maze.height() && maze.solid(x as usize, y as usize)
+    };
+
+    let mut edges = vec![];
+    for y in 0..maze.height() as i64 {
+        for x in 0..maze.width() as i64 {
+            if !solid(x, y) {
+                continue;
+            }
+
+            let left = origin.0 + x as f32 * tile_size;
+            let top = origin.1 + y as f32 * tile_size;
+            let (right, bottom) = (left + tile_size, top + tile_size);
+
+            if !solid(x, y - 1) {
+                edges.push(((left, top), (right, top)));
+            }
+            if !solid(x + 1, y) {
+                edges.push(((right, top), (right, bottom)));
+            }
+            if !solid(x, y + 1) {
+                edges.push(((right, bottom), (left, bottom)));
+            }
+            if !solid(x - 1, y) {
+                edges.push(((left, bottom), (left, top)));
+            }
+        }
+    }
+
+    edges
+}
+
+// the shortest distance from a point to an edge
+fn segment_distance(point: (f32, f32), edge: Edge) -> f32 {
+    let ((ax, ay), (bx, by)) = edge;
+    let (dx, dy) = (bx - ax, by - ay);
+    let length = dx * dx + dy * dy;
+
+    let t = if length > 0.0 {
+        (((point.0 - ax) * dx + (point.1 - ay) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let (x, y) = (ax + t * dx, ay + t * dy);
+    ((point.0 - x).powi(2) + (point.1 - y).powi(2)).sqrt()
+}
+
+// the positions a light is drawn from,
+// a soft light is spread over a circle of its softness
+fn light_samples(light: &Light) -> Vec<(f32, f32)> {
+    let (x, y) = light.position;
+    if light.softness <= 0.0 {
+        return vec![(x, y)];
+    }
+
+    (0..SOFT_SAMPLES)
+        .map(|i| {
+            let angle = i as f32 / SOFT_SAMPLES as f32 * std::f32::consts::TAU;
+            (x + angle.cos() * light.softness, y + angle.sin() * light.softness)
+        })
+        .collect()
+}
+
+// the two triangles of the square around a light
+fn light_quad(center: (f32, f32), radius: f32) -> Vec<f32> {
+    let (left, top) = (center.0 - radius, center.1 - radius);
+    let (right, bottom) = (center.0 + radius, center.1 + radius);
+
+    vec![
+        left, top, right, top, right, bottom,
+        left, top, right, bottom, left, bottom,
+    ]
+}
+
+/*
+the triangles of the shadows of edges from a light,
+every edge is pushed away from the light to twice
+the radius and gets a third point in the middle
+so the shadow still leaves the radius when
+the light is close to the edge
+*/
+fn shadow_triangles(light: (f32, f32), radius: f32, edges: &[Edge]) -> Vec<f32> {
+    let far = radius * 2.0;
+    let push = |point: (f32, f32)| {
+        let (dx, dy) = (point.0 - light.0, point.1 - light.1);
+        let length = (dx * dx + dy * dy).sqrt().max(f32::EPSILON);
+        (light.0 + dx / length * far, light.1 + dy / length * far)
+    };
+
+    let mut vertices = vec![];
+    for &(a, b) in edges {
+        let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        // an edge through the light has no shadow
+        if segment_distance(light, (a, b)) <= f32::EPSILON {
+            continue;
+        }
+
+        let (far_a, far_b, far_middle) = (push(a), push(b), push(middle));
+        for (p, q, r) in [(a, b, far_b), (a, far_b, far_middle), (a, far_middle, far_a)] {
+            vertices.extend_from_slice(&[p.0, p.1, q.0, q.1, r.0, r.1]);
+        }
+    }
+
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Room {}
+
+    // a 3x3 block of walls with a hole in the middle
+    impl Maze for Room {
+        fn width(&self) -> usize {
+            3
+        }
+
+        fn height(&self) -> usize {
+            3
+        }
+
+        fn solid(&self, x: usize, y: usize) -> bool {
+            !(x == 1 && y == 1)
+        }
+    }
+
+    // is the point in one of the triangles
+    fn covered(vertices: &[f32], point: (f32, f32)) -> bool {
+        let side = |a: (f32, f32), b: (f32, f32)| (b.0 - a.0) * (point.1 - a.1) - (b.1 - a.1) * (point.0 - a.0);
+
+        vertices.chunks(6).any(|t| {
+            let (a, b, c) = ((t[0], t[1]), (t[2], t[3]), (t[4], t[5]));
+            let sides = [side(a, b), side(b, c), side(c, a)];
+            sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+        })
+    }
+
+    #[test]
+    fn test_maze_edges() {
+        let edges = maze_edges(&Room {}, 10.0, (100.0, 0.0));
+        // the outside is 12 sides and the hole 4
+        assert_eq!(16, edges.len());
+        assert!(edges.contains(&((100.0, 0.0), (110.0, 0.0))));
+        // the top of the hole is the bottom of the tile above it
+        assert!(edges.contains(&((120.0, 10.0), (110.0, 10.0))));
+    }
+
+    #[test]
+    fn test_polygon_edges() {
+        let edges = polygon_edges(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        assert_eq!(vec![((0.0, 0.0), (10.0, 0.0)), ((10.0, 0.0), (10.0, 10.0)), ((10.0, 10.0), (0.0, 0.0))], edges);
+        assert!(polygon_edges(&[(0.0, 0.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_shadow_triangles() {
+        let edge = ((10.0, -5.0), (10.0, 5.0));
+        let vertices = shadow_triangles((0.0, 0.0), 100.0, &[edge]);
+        assert_eq!(3 * 6, vertices.len());
+
+        // behind the edge is dark, in front and beside it is not
+        assert!(covered(&vertices, (50.0, 0.0)));
+        assert!(covered(&vertices, (99.0, 40.0)));
+        assert!(!covered(&vertices, (5.0, 0.0)));
+        assert!(!covered(&vertices, (50.0, 40.0)));
+
+        // close to a long edge the shadow still leaves the radius
+        let vertices = shadow_triangles((0.0, 0.0), 100.0, &[((1.0, -500.0), (1.0, 500.0))]);
+        assert!(covered(&vertices, (99.0, 0.0)));
+        assert!(covered(&vertices, (60.0, 60.0)));
+    }
+
+    #[test]
+    fn test_segment_distance() {
+        let edge = ((0.0, 0.0), (10.0, 0.0));
+        assert_eq!(5.0, segment_distance((5.0, 5.0), edge));
+        assert_eq!(5.0, segment_distance((13.0, 4.0), edge));
+    }
+}
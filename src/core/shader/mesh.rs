@@ -0,0 +1,431 @@
+use crate::core::math::mvp;
+use crate::core::math::transform::Transform;
+use crate::core::project::Draw;
+use crate::core::shader::object::{Buffer, VertexArray};
+use crate::core::shader::program::Program;
+use crate::core::shader::Shader;
+use gl::types::*;
+use std::collections::BTreeMap;
+
+// the program of a mesh without its own program,
+// every vertex has a position and can have a color
+const VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in vec2 position;
+    layout (location = 1) in vec4 color;
+
+    uniform mat4 projection;
+    uniform mat4 view;
+    uniform mat4 model;
+
+    out vec4 oColor;
+
+    void main() {
+        gl_Position = projection * view * model * vec4(position, 0.0, 1.0);
+        oColor = color;
+    }
+"#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330
+    in vec4 oColor;
+
+    out vec4 color;
+
+    void main() {
+        color = oColor;
+    }
+"#;
+
+/// a value of a vertex, the attributes of a layout
+/// get the locations 0, 1, 2, ... in the vertex shader
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Attribute {
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+impl Attribute {
+    // the number of floats of the attribute
+    fn components(&self) -> usize {
+        match self {
+            Attribute::Float => 1,
+            Attribute::Vec2 => 2,
+            Attribute::Vec3 => 3,
+            Attribute::Vec4 => 4,
+        }
+    }
+}
+
+/// how the vertices of a mesh are connected
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Primitive {
+    // every 3 vertices are a triangle
+    #[default]
+    Triangles,
+    // every vertex makes a triangle with the 2 before it
+    TriangleStrip,
+    // every vertex makes a triangle with the one before it and the first
+    TriangleFan,
+    // every 2 vertices are a line
+    Lines,
+    // a line through all vertices
+    LineStrip,
+    // a line through all vertices back to the first
+    LineLoop,
+    // every vertex is a pixel
+    Points,
+}
+
+impl Primitive {
+    // the primitive the way opengl wants it
+    fn gl(&self) -> GLenum {
+        match self {
+            Primitive::Triangles => gl::TRIANGLES,
+            Primitive::TriangleStrip => gl::TRIANGLE_STRIP,
+            Primitive::TriangleFan => gl::TRIANGLE_FAN,
+            Primitive::Lines => gl::LINES,
+            Primitive::LineStrip => gl::LINE_STRIP,
+            Primitive::LineLoop => gl::LINE_LOOP,
+            Primitive::Points => gl::POINTS,
+        }
+    }
+}
+
+/// how often the vertices of a mesh change
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Usage {
+    // set once and drawn many times
+    #[default]
+    Static,
+    // changed often, e.g. every frame
+    Dynamic,
+}
+
+impl Usage {
+    // the usage the way opengl wants it
+    fn gl(&self) -> GLenum {
+        match self {
+            Usage::Static => gl::STATIC_DRAW,
+            Usage::Dynamic => gl::DYNAMIC_DRAW,
+        }
+    }
+}
+
+/// Collects everything a Mesh needs before it is sent to the graphics card
+/// ```rust
+/// // a triangle with a color on every corner
+/// let mut triangle = MeshBuilder::new(&[Attribute::Vec2, Attribute::Vec4])
+///     .vertices(&[
+///         0.0, 100.0, 1.0, 0.0, 0.0, 1.0,
+///         50.0, 0.0, 0.0, 1.0, 0.0, 1.0,
+///         100.0, 100.0, 0.0, 0.0, 1.0, 1.0,
+///     ])
+///     .build()
+///     .unwrap();
+///
+/// // a quad of 4 vertices and 6 indices with its own program
+/// let water = MeshBuilder::new(&[Attribute::Vec2, Attribute::Vec2])
+///     .vertices(&quad)
+///     .indices(&[0, 1, 2, 0, 2, 3])
+///     .program(WATER_VERTEX, WATER_FRAGMENT)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct MeshBuilder {
+    layout: Vec<Attribute>,
+    vertices: Vec<f32>,
+    indices: Option<Vec<u32>>,
+    primitive: Primitive,
+    usage: Usage,
+    program: Option<(String, String)>,
+}
+
+impl MeshBuilder {
+    // start a mesh where every vertex has the attributes of layout
+    pub fn new(layout: &[Attribute]) -> Self {
+        Self {
+            layout: layout.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    // the vertices, one after another with all attributes of the layout
+    pub fn vertices(mut self, vertices: &[f32]) -> Self {
+        self.vertices = vertices.to_vec();
+        self
+    }
+
+    // draw the vertices in the order of indices
+    pub fn indices(mut self, indices: &[u32]) -> Self {
+        self.indices = Some(indices.to_vec());
+        self
+    }
+
+    // how the vertices are connected, triangles by default
+    pub fn primitive(mut self, primitive: Primitive) -> Self {
+        self.primitive = primitive;
+        self
+    }
+
+    // how often the vertices change, static by default
+    pub fn usage(mut self, usage: Usage) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /*
+    draw with a program of vertex and fragment shader source code,
+    the vertex shader gets the attributes of the layout and can use
+        uniform mat4 projection;
+        uniform mat4 view;      the camera
+        uniform mat4 model;     the transform of the mesh
+    */
+    pub fn program(mut self, vertex_source: &str, fragment_source: &str) -> Self {
+        self.program = Some((vertex_source.to_string(), fragment_source.to_string()));
+        self
+    }
+
+    // compile the program and send the mesh to the graphics card
+    pub fn build(self) -> Result<Mesh, String> {
+        if self.layout.is_empty() {
+            return Err("A Mesh needs at least one attribute.".to_string());
+        }
+
+        let (vertex_source, fragment_source) = match &self.program {
+            Some((vertex_source, fragment_source)) => (vertex_source.as_str(), fragment_source.as_str()),
+            None => (VERTEX_SHADER_SOURCE, FRAGMENT_SHADER_SOURCE),
+        };
+
+        let vertex_shader = Shader::new(vertex_source, gl::VERTEX_SHADER)?;
+        let fragment_shader = Shader::new(fragment_source, gl::FRAGMENT_SHADER)?;
+
+        let mut mesh = Mesh {
+            transform: Transform::new(),
+            program: Program::new(&vertex_shader, &fragment_shader)?,
+            vertex_array: VertexArray::new(),
+            vertex_buffer: Buffer::new(gl::ARRAY_BUFFER, self.usage.gl()),
+            index_buffer: Buffer::new(gl::ELEMENT_ARRAY_BUFFER, self.usage.gl()),
+            layout: self.layout,
+            primitive: self.primitive,
+            vertex_count: 0,
+            index_count: None,
+            index_max: 0,
+            default_program: self.program.is_none(),
+            uniforms: BTreeMap::new(),
+        };
+
+        mesh.vertex_array.bind();
+        mesh.vertex_buffer.bind();
+
+        // create the attributes in the vertex shader
+        let (stride, offsets) = layout_offsets(&mesh.layout);
+        for (location, (attribute, offset)) in mesh.layout.iter().zip(offsets).enumerate() {
+            unsafe {
+                gl::VertexAttribPointer(
+                    location as GLuint,
+                    attribute.components() as GLint,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    (stride * std::mem::size_of::<f32>()) as GLsizei,
+                    (offset * std::mem::size_of::<f32>()) as *const GLvoid,
+                );
+                gl::EnableVertexAttribArray(location as GLuint);
+            }
+        }
+
+        mesh.set_vertices(&self.vertices)?;
+        if let Some(indices) = &self.indices {
+            mesh.set_indices(indices)?;
+        }
+
+        Ok(mesh)
+    }
+}
+
+/*
+A Mesh is geometry that is not a shape of the engine,
+its vertices can have any attributes and are drawn
+with the default program or a program of its own
+*/
+pub struct Mesh {
+    pub transform: Transform,
+    program: Program,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    layout: Vec<Attribute>,
+    primitive: Primitive,
+    vertex_count: usize,
+    // None draws the vertices in order
+    index_count: Option<usize>,
+    // the highest index, it needs a vertex
+    index_max: usize,
+    default_program: bool,
+    // values for the uniforms of a custom program
+    uniforms: BTreeMap<String, Vec<f32>>,
+}
+
+impl Mesh {
+    // replace all vertices, indices have to
+    // point to vertices that still exist
+    pub fn set_vertices(&mut self, vertices: &[f32]) -> Result<(), String> {
+        let (stride, _) = layout_offsets(&self.layout);
+        let count = vertex_count(stride, vertices.len())?;
+        if self.index_count.is_some() && self.index_max >= count {
+            return Err(format!("Index {} is out of range of {} vertices.", self.index_max, count));
+        }
+
+        self.vertex_count = count;
+
+        self.vertex_array.bind();
+        self.vertex_buffer.set_data(&vertices.to_vec());
+        Ok(())
+    }
+
+    // draw the vertices in the order of indices
+    pub fn set_indices(&mut self, indices: &[u32]) -> Result<(), String> {
+        check_indices(indices, self.vertex_count)?;
+
+        // the index buffer belongs to the vertex array
+        self.vertex_array.bind();
+        self.index_buffer.set_data(&indices.to_vec());
+        self.index_count = Some(indices.len());
+        self.index_max = indices.iter().max().map_or(0, |i| *i as usize);
+        Ok(())
+    }
+
+    // draw the vertices in order again
+    pub fn remove_indices(&mut self) {
+        self.index_count = None;
+    }
+
+    // change how the vertices are connected
+    pub fn set_primitive(&mut self, primitive: Primitive) {
+        self.primitive = primitive;
+    }
+
+    // get how the vertices are connected
+    pub fn primitive(&self) -> Primitive {
+        self.primitive
+    }
+
+    // the number of vertices
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    // give a uniform of the program 1 to 4 floats or a mat4 of 16,
+    // the value is sent every draw
+    pub fn set_uniform(&mut self, name: &str, value: &[f32]) -> Result<(), String> {
+        match value.len() {
+            1..=4 | 16 => {
+                self.uniforms.insert(name.to_string(), value.to_vec());
+                Ok(())
+            }
+            length => Err(format!("Uniform '{}' cannot have {} values.", name, length)),
+        }
+    }
+
+    // draw the mesh to the screen
+    pub fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        // create the mvp (model view projection) matrixes
+        let projection = mvp::ortho(&draw.window);
+        let view = camera.matrix();
+        let model = self.transform.matrix();
+
+        unsafe {
+            self.program.bind();
+            self.vertex_array.bind();
+
+            let projection_location = self.program.get_uniform_location("projection")?;
+            let view_location = self.program.get_uniform_location("view")?;
+            let model_location = self.program.get_uniform_location("model")?;
+            gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
+            gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
+            gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
+
+            for (name, value) in &self.uniforms {
+                let location = self.program.get_uniform_location(name)?;
+                match value.len() {
+                    1 => gl::Uniform1fv(location, 1, value.as_ptr()),
+                    2 => gl::Uniform2fv(location, 1, value.as_ptr()),
+                    3 => gl::Uniform3fv(location, 1, value.as_ptr()),
+                    4 => gl::Uniform4fv(location, 1, value.as_ptr()),
+                    _ => gl::UniformMatrix4fv(location, 1, gl::FALSE, value.as_ptr()),
+                }
+            }
+
+            // vertices without a color are white
+            if self.default_program && self.layout.len() < 2 {
+                gl::VertexAttrib4f(1, 1.0, 1.0, 1.0, 1.0);
+            }
+
+            match self.index_count {
+                Some(count) => gl::DrawElements(self.primitive.gl(), count as GLsizei, gl::UNSIGNED_INT, std::ptr::null()),
+                None => gl::DrawArrays(self.primitive.gl(), 0, self.vertex_count as GLsizei),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// the number of floats of a vertex
+// and where every attribute starts in it
+fn layout_offsets(layout: &[Attribute]) -> (usize, Vec<usize>) {
+    let mut offsets = vec![];
+    let mut stride = 0;
+    for attribute in layout {
+        offsets.push(stride);
+        stride += attribute.components();
+    }
+
+    (stride, offsets)
+}
+
+// the number of vertices in length floats,
+// a stride of 0 has no vertices
+fn vertex_count(stride: usize, length: usize) -> Result<usize, String> {
+    if length.checked_rem(stride) != Some(0) {
+        return Err(format!("{} floats are not vertices of {} floats.", length, stride));
+    }
+
+    Ok(length / stride)
+}
+
+// make sure every index points to a vertex
+fn check_indices(indices: &[u32], vertex_count: usize) -> Result<(), String> {
+    match indices.iter().find(|i| **i as usize >= vertex_count) {
+        Some(i) => Err(format!("Index {} is out of range of {} vertices.", i, vertex_count)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_offsets() {
+        let (stride, offsets) = layout_offsets(&[Attribute::Vec2, Attribute::Vec4, Attribute::Float]);
+        assert_eq!(7, stride);
+        assert_eq!(vec![0, 2, 6], offsets);
+    }
+
+    #[test]
+    fn test_vertex_count() {
+        assert_eq!(Ok(3), vertex_count(6, 18));
+        assert_eq!(Ok(0), vertex_count(2, 0));
+        assert!(vertex_count(6, 20).is_err());
+    }
+
+    #[test]
+    fn test_check_indices() {
+        assert!(check_indices(&[0, 1, 2, 0, 2, 3], 4).is_ok());
+        assert!(check_indices(&[0, 1, 4], 4).is_err());
+    }
+}
@@ -1,8 +1,13 @@
 use gl::types::*;
 
-use crate::{Draw, ObjectData, Transform};
+use crate::{Draw, Image, ObjectData, Transform};
+use line::Polyline;
+use polygon::PolygonData;
 
 pub mod circle;
+pub mod line;
+pub mod nineslice;
+pub mod polygon;
 pub mod rect;
 pub mod text;
 pub mod texture;
@@ -58,6 +63,40 @@ impl Buffer {
     }
 }
 
+impl Buffer {
+    // reserve size bytes on the graphics card without data,
+    // the old data of the buffer is lost
+    pub fn allocate(&self, size: usize) {
+        self.bind();
+
+        unsafe {
+            gl::BufferData(
+                self.target,
+                size as gl::types::GLsizeiptr,
+                std::ptr::null(),
+                self.usage,
+            );
+        }
+    }
+
+    // overwrite a part of the buffer with data,
+    // the offset is counted in elements of the data type
+    pub fn set_sub_data<DataType>(&self, offset: usize, data: &[DataType]) {
+        self.bind();
+        let offset = offset * std::mem::size_of::<DataType>();
+        let size = std::mem::size_of_val(data);
+
+        unsafe {
+            gl::BufferSubData(
+                self.target,
+                offset as gl::types::GLintptr,
+                size as gl::types::GLsizeiptr,
+                data.as_ptr() as *const gl::types::GLvoid,
+            );
+        }
+    }
+}
+
 impl Default for Buffer {
     // get an empty buffer
     fn default() -> Self {
@@ -79,6 +118,241 @@ impl Drop for Buffer {
     }
 }
 
+/*
+an InstanceBuffer holds the transform data of all instances
+on the cpu and in a buffer on the graphics card
+
+only the instances that changed since the last upload
+are sent again, the buffer on the graphics card grows
+in steps so adding instances does not reallocate it every time
+*/
+pub struct InstanceBuffer<DataType> {
+    buffer: Buffer,
+    data: Vec<DataType>,
+    // the number of instances that fit into the buffer on the graphics card
+    capacity: usize,
+    // the ranges of instances that changed
+    dirty: Vec<(usize, usize)>,
+    // the instances that get drawn, None draws all
+    visible: Option<Vec<usize>>,
+    visible_changed: bool,
+    // the graphics card holds only the visible instances
+    uploaded_visible: bool,
+    // the data of the visible instances, kept to reuse the allocation
+    visible_data: Vec<DataType>,
+}
+
+// the smallest number of instances the buffer has space for
+const MIN_INSTANCE_CAPACITY: usize = 16;
+
+impl<DataType: Copy> InstanceBuffer<DataType> {
+    // create an empty InstanceBuffer
+    // without a buffer on the graphics card
+    pub fn new() -> Self {
+        Self {
+            buffer: Buffer::default(),
+            data: vec![],
+            capacity: 0,
+            dirty: vec![],
+            visible: None,
+            visible_changed: false,
+            uploaded_visible: false,
+            visible_data: vec![],
+        }
+    }
+
+    // create the buffer on the graphics card and upload all instances,
+    // the buffer stays bound so the attributes can be set up
+    pub fn load(&mut self) {
+        self.buffer = Buffer::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW);
+        self.capacity = 0;
+        self.visible_changed = true;
+        self.upload();
+        self.buffer.bind();
+    }
+
+    // the number of instances
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    // true if there are no instances
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    // add an instance to the end
+    pub fn push(&mut self, data: DataType) {
+        self.data.push(data);
+        self.mark(self.data.len() - 1, self.data.len());
+    }
+
+    // replace instance i
+    pub fn set(&mut self, i: usize, data: DataType) {
+        self.data[i] = data;
+        self.mark(i, i + 1);
+    }
+
+    // remove instance i,
+    // all instances after it move one to the front
+    pub fn remove(&mut self, i: usize) {
+        self.data.remove(i);
+        self.mark(i, self.data.len());
+    }
+
+    // remove instance i by moving
+    // the last instance into its place
+    pub fn swap_remove(&mut self, i: usize) {
+        self.data.swap_remove(i);
+        self.mark(i, (i + 1).min(self.data.len()));
+    }
+
+    // replace the instances from start to end with data,
+    // the instances after it move when the length changes
+    pub fn splice(&mut self, start: usize, end: usize, data: &[DataType]) {
+        if end - start == data.len() {
+            self.data[start..end].copy_from_slice(data);
+            self.mark(start, end);
+        } else {
+            self.data.splice(start..end, data.iter().copied());
+            self.mark(start, self.data.len());
+        }
+    }
+
+    // remove all instances,
+    // the buffer on the graphics card keeps its size
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.dirty.clear();
+    }
+
+    // replace all instances at once
+    pub fn replace(&mut self, data: Vec<DataType>) {
+        self.data = data;
+        self.dirty = vec![(0, self.data.len())];
+    }
+
+    // the number of instances that get drawn
+    pub fn draw_count(&self) -> usize {
+        match &self.visible {
+            Some(visible) => visible.len(),
+            None => self.data.len(),
+        }
+    }
+
+    // make the buffer on the graphics card big enough for len instances,
+    // returns true if it was reallocated and lost its data
+    fn reserve(&mut self, len: usize) -> bool {
+        if len <= self.capacity && self.capacity != 0 {
+            return false;
+        }
+
+        // grow the buffer to double its size when it is too small
+        let capacity = len.max(self.capacity * 2).max(MIN_INSTANCE_CAPACITY);
+        self.buffer.allocate(capacity * std::mem::size_of::<DataType>());
+        self.capacity = capacity;
+        true
+    }
+
+    // remember that the instances from start to end changed
+    fn mark(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.dirty.push((start, end));
+        }
+    }
+
+    // only draw the instances in visible, None draws all,
+    // returns true if the buffer needs an upload
+    pub fn set_visible(&mut self, visible: Option<Vec<usize>>) -> bool {
+        if self.visible == visible {
+            return false;
+        }
+
+        self.visible = visible;
+        self.visible_changed = true;
+        true
+    }
+
+    // send the changed instances to the graphics card
+    pub fn upload(&mut self) {
+        if self.visible.is_some() {
+            self.upload_visible();
+            return;
+        }
+
+        let len = self.data.len();
+
+        // all instances need to be sent again after
+        // the buffer grew or held only the visible ones
+        if self.reserve(len) || self.uploaded_visible {
+            self.dirty = vec![(0, len)];
+            self.uploaded_visible = false;
+        }
+
+        for (start, end) in merge_ranges(&mut self.dirty) {
+            let end = end.min(len);
+            if start < end {
+                self.buffer.set_sub_data(start, &self.data[start..end]);
+            }
+        }
+
+        self.dirty.clear();
+    }
+
+    // send the visible instances one after another
+    // to the start of the buffer
+    fn upload_visible(&mut self) {
+        if !self.visible_changed && self.dirty.is_empty() {
+            return;
+        }
+
+        let visible = self.visible.as_deref().unwrap_or_default();
+        self.visible_data.clear();
+        let data = &self.data;
+        self.visible_data.extend(visible.iter().map(|i| data[*i]));
+
+        self.reserve(self.visible_data.len());
+        self.buffer.set_sub_data(0, &self.visible_data);
+
+        self.uploaded_visible = true;
+        self.visible_changed = false;
+        self.dirty.clear();
+    }
+}
+
+impl<DataType: Copy> Default for InstanceBuffer<DataType> {
+    // an empty InstanceBuffer
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the most ranges that are sent one by one,
+// more get sent as a single range
+const MAX_DIRTY_RANGES: usize = 32;
+
+// sort the ranges and join the ones that overlap or touch
+fn merge_ranges(ranges: &mut [(usize, usize)]) -> Vec<(usize, usize)> {
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = vec![];
+    for &(start, end) in ranges.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    // many small uploads are slower than one big upload
+    if merged.len() > MAX_DIRTY_RANGES {
+        let start = merged[0].0;
+        let end = merged.iter().map(|range| range.1).max().unwrap_or(start);
+        return vec![(start, end)];
+    }
+
+    merged
+}
+
 /*
 A VertexArray is all the data in a single draw call
 this can include multiple buffers
@@ -152,20 +426,11 @@ impl TextureBuffer {
 
     // give the buffer its data
     // this data is an image
-    pub fn set_data(&self, image: &image::RgbaImage) {
+    pub fn set_data(&self, image: &image::RgbaImage, options: &TextureOptions) {
         self.bind();
         let (width, height) = image.dimensions();
 
         unsafe {
-            // pixelate the image when scaling down
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-            // pixelate the image when scaling up
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-
-            // fill in empty space around the texture
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-
             // structure of the texture, with width height and texture data
             gl::TexImage2D(
                 gl::TEXTURE_2D,
@@ -178,9 +443,92 @@ impl TextureBuffer {
                 gl::UNSIGNED_BYTE,
                 image.as_ptr() as *const gl::types::GLvoid,
             );
+        }
+
+        self.set_options(options);
+    }
+
+    // replace a part of the texture with an image,
+    // x and y are in the coordinates of opengl which start bottom left
+    pub fn set_sub_data(&self, image: &image::RgbaImage, x: i32, y: i32) {
+        self.bind();
+        let (width, height) = image.dimensions();
+
+        unsafe {
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x,
+                y,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                image.as_ptr() as *const gl::types::GLvoid,
+            );
+        }
+    }
+
+    // set how the texture is sampled when it gets drawn,
+    // needs to be called after the texture has its data
+    pub fn set_options(&self, options: &TextureOptions) {
+        self.bind();
+
+        unsafe {
+            // the filter when scaling down and up
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, options.min_filter() as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, options.mag_filter.gl() as i32);
+
+            // what happens outside of the texture coordinates 0.0 to 1.0
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, options.wrap_x.gl() as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, options.wrap_y.gl() as i32);
 
             // generate the neccessasary mipmap textures, (needed when texture is very near or far away)
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+            if options.mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+
+            // anisotropy is an extension,
+            // if the graphics card does not know it the maximum stays 1.0
+            if options.anisotropy > 1.0 {
+                let mut max_anisotropy = 1.0;
+                gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+                gl::GetError();
+
+                if max_anisotropy > 1.0 {
+                    let anisotropy = options.anisotropy.min(max_anisotropy);
+                    gl::TexParameterf(gl::TEXTURE_2D, TEXTURE_MAX_ANISOTROPY, anisotropy);
+                }
+            }
+        }
+    }
+
+    // reserve an empty texture with width and height
+    // used as the color attachment of a framebuffer
+    pub fn allocate(&self, width: i32, height: i32) {
+        self.bind();
+
+        unsafe {
+            // smooth the image when the target is scaled
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+
+            // fill in empty space around the texture
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+
+            // structure of the texture without any data
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as i32,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
         }
     }
 }
@@ -202,6 +550,258 @@ impl Drop for TextureBuffer {
     }
 }
 
+// the anisotropic filtering constants are not part of opengl 3.3
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+// how a texture gets filtered when it is scaled
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    // pixelate the texture
+    Nearest,
+    // smooth the texture
+    Linear,
+}
+
+impl Filter {
+    // the opengl value of the filter
+    fn gl(&self) -> GLenum {
+        match self {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+// what a texture shows outside of its texture coordinates
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wrap {
+    // repeat the pixels at the edge
+    Clamp,
+    // repeat the texture
+    Repeat,
+    // repeat the texture and mirror every second one
+    MirroredRepeat,
+}
+
+impl Wrap {
+    // the opengl value of the wrapping
+    fn gl(&self) -> GLenum {
+        match self {
+            Wrap::Clamp => gl::CLAMP_TO_EDGE,
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+        }
+    }
+}
+
+/// how a texture is sampled when it gets drawn
+/// ```rust
+/// let mut options = TextureOptions::smooth();
+/// options.wrap_x = Wrap::Repeat;
+/// let background = Shader::texture_with_options(&image, &options).unwrap();
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureOptions {
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    pub wrap_x: Wrap,
+    pub wrap_y: Wrap,
+    // smaller versions of the texture that are used
+    // when it gets scaled down
+    pub mipmaps: bool,
+    // sharper textures at steep angles, 1.0 is off
+    pub anisotropy: f32,
+}
+
+impl TextureOptions {
+    // linear filtering between the pixels and the mipmaps
+    pub fn smooth() -> Self {
+        Self {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmaps: true,
+            ..Self::default()
+        }
+    }
+
+    // the opengl value of the filter when scaling down,
+    // it also chooses between the mipmaps
+    fn min_filter(&self) -> GLenum {
+        match (self.min_filter, self.mipmaps) {
+            (Filter::Nearest, false) => gl::NEAREST,
+            (Filter::Linear, false) => gl::LINEAR,
+            (Filter::Nearest, true) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+impl Default for TextureOptions {
+    // pixelated textures that are clamped at the edges
+    fn default() -> Self {
+        Self {
+            min_filter: Filter::Nearest,
+            mag_filter: Filter::Nearest,
+            wrap_x: Wrap::Clamp,
+            wrap_y: Wrap::Clamp,
+            mipmaps: false,
+            anisotropy: 1.0,
+        }
+    }
+}
+
+/*
+a Renderbuffer is storage on the graphics card that
+a framebuffer can draw into but that cannot be sampled,
+used for the depth and stencil buffer of a framebuffer
+*/
+#[derive(Default)]
+pub struct Renderbuffer {
+    pub id: GLuint,
+}
+
+impl Renderbuffer {
+    // generate the id
+    pub fn new() -> Self {
+        let mut id: GLuint = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut id);
+        }
+        Self { id }
+    }
+
+    // set the renderbuffer to active
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, self.id);
+        }
+    }
+
+    // reserve a depth and stencil buffer with width and height,
+    // a stencil buffer on its own is not supported everywhere
+    pub fn allocate_depth_stencil(&self, width: i32, height: i32) {
+        self.bind();
+
+        unsafe {
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width, height);
+        }
+    }
+}
+
+// delete the renderbuffer on the graphics card
+// when the Renderbuffer gets dropped
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteRenderbuffers(1, [self.id].as_ptr());
+        }
+    }
+}
+
+/*
+a Framebuffer is a render destination on the graphics card
+instead of drawing to the window, opengl draws into
+the texture buffer that is attached to it
+
+it has an id that gets generated by opengl and points to the graphics card
+*/
+pub struct Framebuffer {
+    pub id: GLuint,
+}
+
+impl Framebuffer {
+    // generate the id
+    pub fn new() -> Self {
+        let mut id: GLuint = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut id);
+        }
+        Self { id }
+    }
+
+    // set the framebuffer to active
+    // all draw calls will now end up in its texture
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        }
+    }
+
+    // set the window as the active framebuffer again
+    pub fn unbind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    // use the texture buffer as the color destination
+    // of this framebuffer
+    pub fn attach(&self, texture_buffer: &TextureBuffer) -> Result<(), String> {
+        self.bind();
+
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture_buffer.id,
+                0,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            self.unbind();
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(format!("Framebuffer is not complete (status {}).", status));
+            }
+        }
+
+        Ok(())
+    }
+
+    // use the renderbuffer as the depth and
+    // stencil buffer of this framebuffer
+    pub fn attach_depth_stencil(&self, renderbuffer: &Renderbuffer) -> Result<(), String> {
+        self.bind();
+
+        unsafe {
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                renderbuffer.id,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            self.unbind();
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(format!("Framebuffer is not complete (status {}).", status));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Framebuffer {
+    // an empty Framebuffer
+    fn default() -> Self {
+        Self { id: 0 }
+    }
+}
+
+// delete the framebuffer on the graphics card
+// when the Framebuffer gets dropped
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, [self.id].as_ptr());
+        }
+    }
+}
+
 // Implement this Trait on shader objects
 // so we can use them in the component system
 pub trait Object {
@@ -210,6 +810,7 @@ pub trait Object {
     fn load(&mut self) -> Result<(), String>;
     fn reload(&mut self);
     fn remove(&mut self, i: usize);
+    fn swap_remove(&mut self, i: usize);
     fn remove_all(&mut self);
     fn draw(
         &mut self,
@@ -218,6 +819,53 @@ pub trait Object {
         model_transform: &Transform,
     ) -> Result<(), String>;
     fn set_state(&mut self, object_state: ObjectState);
+
+    // only draw the instances in visible, None draws all,
+    // objects that cannot skip instances always draw all
+    fn set_visible(&mut self, _visible: Option<Vec<usize>>) {}
+
+    // set the component data of all instances at once
+    fn set_all(&mut self, object_data: &[ObjectData]) {
+        for (i, object_data) in object_data.iter().enumerate() {
+            self.set(i, object_data);
+        }
+    }
+
+    // add a polyline, only objects that draw lines support this
+    fn add_polyline(&mut self, _polyline: &Polyline) -> Result<(), String> {
+        Err("This object cannot draw polylines.".to_string())
+    }
+
+    // replace polyline i, only objects that draw lines support this
+    fn set_polyline(&mut self, _i: usize, _polyline: &Polyline) -> Result<(), String> {
+        Err("This object cannot draw polylines.".to_string())
+    }
+
+    // add a polygon, only objects that draw polygons support this
+    fn add_polygon(&mut self, _polygon: &PolygonData) -> Result<(), String> {
+        Err("This object cannot draw polygons.".to_string())
+    }
+
+    // replace polygon i, only objects that draw polygons support this
+    fn set_polygon(&mut self, _i: usize, _polygon: &PolygonData) -> Result<(), String> {
+        Err("This object cannot draw polygons.".to_string())
+    }
+
+    // replace the image, only objects that draw textures support this
+    fn set_image(&mut self, _image: &Image) -> Result<(), String> {
+        Err("This object has no image.".to_string())
+    }
+
+    // replace a part of the image starting at x and y from the top left,
+    // only objects that draw textures support this
+    fn set_sub_image(&mut self, _x: u32, _y: u32, _image: &Image) -> Result<(), String> {
+        Err("This object has no image.".to_string())
+    }
+
+    // change how the texture is sampled, only objects that draw textures support this
+    fn set_texture_options(&mut self, _options: &TextureOptions) -> Result<(), String> {
+        Err("This object has no texture options.".to_string())
+    }
 }
 
 // describes the object state
@@ -232,3 +880,18 @@ pub enum ObjectState {
 // 2 for every corner of the rectangle
 pub type TextureCoordinate = [f32; 8];
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_ranges() {
+        let mut ranges = vec![(5, 6), (0, 1), (1, 2), (4, 6)];
+        assert_eq!(vec![(0, 2), (4, 6)], merge_ranges(&mut ranges));
+
+        let mut ranges: Vec<(usize, usize)> = (0..100).map(|i| (i * 2, i * 2 + 1)).collect();
+        assert_eq!(vec![(0, 199)], merge_ranges(&mut ranges));
+
+        assert!(merge_ranges(&mut []).is_empty());
+    }
+}
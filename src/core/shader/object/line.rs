@@ -0,0 +1,788 @@
+use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, InstanceBuffer};
+use crate::core::shader::program::Program;
+use crate::core::shader::data::ObjectData;
+use crate::core::math::mvp;
+use crate::core::math::culling::{self, Bounds};
+use crate::core::project::Draw;
+use crate::core::math::transform::Transform;
+use crate::core::color::Color;
+use crate::core::shader::Shader;
+
+const VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in float corner;
+    layout (location = 1) in vec4 position_1;
+    layout (location = 2) in vec4 position_2;
+    layout (location = 3) in vec4 local_1;
+    layout (location = 4) in vec4 local_2;
+    layout (location = 5) in vec4 color;
+    layout (location = 6) in vec4 params;
+
+    uniform mat4 projection;
+    uniform mat4 view;
+    uniform mat4 model;
+
+    out vec4 oColor;
+    out vec2 oLocal;
+    out vec3 oParams;
+
+    void main() {
+        vec2 position;
+        vec2 local;
+
+        if(corner < 0.5) {
+            position = position_1.xy;
+            local = local_1.xy;
+        } else if(corner < 1.5) {
+            position = position_1.zw;
+            local = local_1.zw;
+        } else if(corner < 2.5) {
+            position = position_2.xy;
+            local = local_2.xy;
+        } else {
+            position = position_2.zw;
+            local = local_2.zw;
+        }
+
+        gl_Position = projection * view * model * vec4(position, 0.0, 1.0);
+        oColor = color;
+        oLocal = local;
+        oParams = params.xyz;
+    }
+"#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330
+    in vec4 oColor;
+    in vec2 oLocal;
+    in vec3 oParams;
+
+    out vec4 color;
+
+    void main() {
+        float dist;
+
+        if(oParams.x < 0.5) {
+            // a band, x is the distance along the line
+            // y goes from -1 to 1 across the line
+            float dash = oParams.y;
+            float gap = oParams.z;
+            if(dash > 0.0 && mod(oLocal.x, dash + gap) > dash) {
+                discard;
+            }
+
+            dist = abs(oLocal.y);
+        } else {
+            // a disc for round caps and joins
+            dist = length(oLocal);
+        }
+
+        float delta = fwidth(dist);
+        float alpha = smoothstep(1.0 - delta, 1.0, dist);
+        color = mix(oColor, vec4(oColor.rgb, 0.0), alpha);
+    }
+"#;
+
+// how two segments of a Polyline are connected
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+// how the ends of a Polyline look
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+/// A line through multiple points
+/// every segment between two points has its own thickness and color
+/// ```rust
+/// let mut polyline = Polyline::new(&[(10.0, 10.0), (200.0, 40.0), (250.0, 200.0)], 6.0, &Color::rgb(255, 0, 0));
+/// polyline.join = LineJoin::Round;
+/// polyline.cap = LineCap::Round;
+/// polyline.dash = Some((12.0, 6.0));
+/// polyline.set_segment_color(1, &Color::rgb(0, 0, 255)).unwrap();
+/// let mut line = Shader::polyline(&polyline).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Polyline {
+    pub points: Vec<(f32, f32)>,
+    pub thickness: Vec<f32>,
+    pub colors: Vec<Color>,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    // the length of a dash and the gap after it
+    pub dash: Option<(f32, f32)>,
+    // miter joins longer than miter_limit * thickness become bevel joins
+    pub miter_limit: f32,
+}
+
+impl Polyline {
+    // create a polyline through the points
+    // where every segment has the same thickness and color
+    pub fn new(points: &[(f32, f32)], thickness: f32, color: &Color) -> Self {
+        let segments = points.len().saturating_sub(1);
+
+        Self {
+            points: points.to_vec(),
+            thickness: vec![thickness; segments],
+            colors: vec![*color; segments],
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            dash: None,
+            miter_limit: 4.0,
+        }
+    }
+
+    // create a polyline with a single segment
+    pub fn line(x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: &Color) -> Self {
+        Self::new(&[(x1, y1), (x2, y2)], thickness, color)
+    }
+
+    // create a polyline through the center of the tiles of a path
+    // e.g. the result of the astar pathfinding
+    pub fn path(path: &[(usize, usize)], tile_size: f32, thickness: f32, color: &Color) -> Self {
+        let points: Vec<(f32, f32)> = path
+            .iter()
+            .map(|(x, y)| ((*x as f32 + 0.5) * tile_size, (*y as f32 + 0.5) * tile_size))
+            .collect();
+
+        Self::new(&points, thickness, color)
+    }
+
+    // the number of segments
+    pub fn segments(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    // set the thickness of segment i
+    pub fn set_segment_thickness(&mut self, i: usize, thickness: f32) -> Result<(), String> {
+        self.index_oob(i)?;
+        self.thickness[i] = thickness;
+        Ok(())
+    }
+
+    // set the color of segment i
+    pub fn set_segment_color(&mut self, i: usize, color: &Color) -> Result<(), String> {
+        self.index_oob(i)?;
+        self.colors[i] = *color;
+        Ok(())
+    }
+
+    // set the color of all segments
+    pub fn set_color(&mut self, color: &Color) {
+        self.colors = vec![*color; self.segments()];
+    }
+
+    // set the thickness of all segments
+    pub fn set_thickness(&mut self, thickness: f32) {
+        self.thickness = vec![thickness; self.segments()];
+    }
+
+    // the rectangle around the points
+    pub fn bounds(&self) -> Bounds {
+        culling::point_bounds(&self.points)
+    }
+
+    // move all points by x and y
+    pub(crate) fn translate(&mut self, x: f32, y: f32) {
+        for point in &mut self.points {
+            *point = (point.0 + x, point.1 + y);
+        }
+    }
+
+    // create the quads that are drawn for the polyline
+    // segments that have no length are skipped
+    fn quads(&self) -> Vec<LineQuad> {
+        let mut quads = vec![];
+        let mut along = 0.0;
+
+        // the segments with length, their direction and normal
+        let mut segments = vec![];
+        for i in 0..self.segments() {
+            let (x1, y1) = self.points[i];
+            let (x2, y2) = self.points[i + 1];
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            if length > f32::EPSILON {
+                let direction = ((x2 - x1) / length, (y2 - y1) / length);
+                let normal = (-direction.1, direction.0);
+                let width = self.thickness.get(i).copied().unwrap_or(1.0) / 2.0;
+                let color = self.colors.get(i).copied().unwrap_or_default();
+                segments.push(Segment {
+                    start: self.points[i],
+                    end: self.points[i + 1],
+                    direction,
+                    normal,
+                    width,
+                    color,
+                    length,
+                });
+            }
+        }
+
+        let count = segments.len();
+        for (i, segment) in segments.iter().enumerate() {
+            let Segment { start, end, direction, normal, width, color, length } = *segment;
+
+            // corners at the start of the segment
+            let (mut start_left, mut start_right) = offset_point(start, normal, width);
+            let mut start_along = along;
+
+            if i == 0 {
+                if self.cap == LineCap::Square {
+                    let start = (start.0 - direction.0 * width, start.1 - direction.1 * width);
+                    let (left, right) = offset_point(start, normal, width);
+                    start_left = left;
+                    start_right = right;
+                    start_along -= width;
+                }
+            } else if let Some(miter) = self.miter(&segments[i - 1], &segments[i]) {
+                start_left = (start.0 + miter.0, start.1 + miter.1);
+                start_right = (start.0 - miter.0, start.1 - miter.1);
+            }
+
+            // corners at the end of the segment
+            let (mut end_left, mut end_right) = offset_point(end, normal, width);
+            let mut end_along = along + length;
+
+            if i + 1 == count {
+                if self.cap == LineCap::Square {
+                    let end = (end.0 + direction.0 * width, end.1 + direction.1 * width);
+                    let (left, right) = offset_point(end, normal, width);
+                    end_left = left;
+                    end_right = right;
+                    end_along += width;
+                }
+            } else if let Some(miter) = self.miter(&segments[i], &segments[i + 1]) {
+                end_left = (end.0 + miter.0, end.1 + miter.1);
+                end_right = (end.0 - miter.0, end.1 - miter.1);
+            }
+
+            quads.push(LineQuad {
+                corners: [start_left, start_right, end_right, end_left],
+                local: [(start_along, 1.0), (start_along, -1.0), (end_along, -1.0), (end_along, 1.0)],
+                color,
+                disc: false,
+            });
+
+            // fill the gap to the next segment
+            if i + 1 < count && self.miter(&segments[i], &segments[i + 1]).is_none() {
+                let next = &segments[i + 1];
+
+                if self.join == LineJoin::Round {
+                    if self.in_dash(along + length) {
+                        quads.push(LineQuad::disc(end, width.max(next.width), color));
+                    }
+                } else {
+                    // the gap is on the outer side of the turn
+                    let cross = direction.0 * next.direction.1 - direction.1 * next.direction.0;
+                    let side = if cross > 0.0 { -1.0 } else { 1.0 };
+                    let a = (end.0 + normal.0 * width * side, end.1 + normal.1 * width * side);
+                    let b = (end.0 + next.normal.0 * next.width * side, end.1 + next.normal.1 * next.width * side);
+                    let along = along + length;
+
+                    quads.push(LineQuad {
+                        corners: [end, a, b, b],
+                        local: [(along, 0.0); 4],
+                        color: next.color,
+                        disc: false,
+                    });
+                }
+            }
+
+            along += length;
+        }
+
+        // round caps at both ends of the polyline,
+        // discs in a gap of the dashes are left out
+        if self.cap == LineCap::Round {
+            if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+                if self.in_dash(0.0) {
+                    quads.push(LineQuad::disc(first.start, first.width, first.color));
+                }
+                if self.in_dash(along) {
+                    quads.push(LineQuad::disc(last.end, last.width, last.color));
+                }
+            }
+        }
+
+        quads
+    }
+
+    // true if the point at a distance along the line is in a dash,
+    // the same test the fragment shader does for bands
+    fn in_dash(&self, along: f32) -> bool {
+        match self.dash {
+            Some((dash, gap)) if dash > 0.0 => along % (dash + gap) <= dash,
+            _ => true,
+        }
+    }
+
+    // the offset from a joint to the miter corner
+    // none if the segments are not connected with a miter
+    fn miter(&self, from: &Segment, to: &Segment) -> Option<(f32, f32)> {
+        if self.join != LineJoin::Miter {
+            return None;
+        }
+
+        let (n1, n2) = (from.normal, to.normal);
+        let sum = (n1.0 + n2.0, n1.1 + n2.1);
+        let length = (sum.0.powi(2) + sum.1.powi(2)).sqrt();
+
+        // the segments go back the way they came
+        if length < f32::EPSILON {
+            return None;
+        }
+
+        let miter = (sum.0 / length, sum.1 / length);
+        let width = from.width.max(to.width);
+        let dot = miter.0 * n2.0 + miter.1 * n2.1;
+        let miter_length = width / dot;
+
+        if miter_length > self.miter_limit * width {
+            return None;
+        }
+
+        Some((miter.0 * miter_length, miter.1 * miter_length))
+    }
+
+    // checks if a segment is in the polyline
+    fn index_oob(&self, i: usize) -> Result<(), String> {
+        if i >= self.segments() {
+            Err(format!("Segment with index '{}' not found.", i))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// a segment of a polyline that has a length
+#[derive(Clone, Copy)]
+struct Segment {
+    start: (f32, f32),
+    end: (f32, f32),
+    direction: (f32, f32),
+    normal: (f32, f32),
+    width: f32,
+    color: Color,
+    length: f32,
+}
+
+// the left and right corner of a point
+// moved along the normal by width
+fn offset_point(point: (f32, f32), normal: (f32, f32), width: f32) -> ((f32, f32), (f32, f32)) {
+    (
+        (point.0 + normal.0 * width, point.1 + normal.1 * width),
+        (point.0 - normal.0 * width, point.1 - normal.1 * width),
+    )
+}
+
+// a single quad that is drawn
+// the local coordinates are used for anti aliasing and dashes
+#[derive(Clone, Copy)]
+struct LineQuad {
+    corners: [(f32, f32); 4],
+    local: [(f32, f32); 4],
+    color: Color,
+    disc: bool,
+}
+
+impl LineQuad {
+    // a disc around a point, used for round joins and caps
+    fn disc(center: (f32, f32), radius: f32, color: Color) -> Self {
+        let (x, y) = center;
+        Self {
+            corners: [
+                (x - radius, y - radius),
+                (x + radius, y - radius),
+                (x + radius, y + radius),
+                (x - radius, y + radius),
+            ],
+            local: [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)],
+            color,
+            disc: true,
+        }
+    }
+}
+
+/*
+Line is an instanced
+implementation of a the shaders above
+it holds all the data that is used for drawing polylines to
+the screen, every polyline is drawn as multiple quads
+
+when line gets dropped the shader also gets
+deletet from the graphics card
+*/
+type TransformData = [f32; 24];
+
+// a polyline and the object data it was added with,
+// lines added with object data go from the offset to offset + dim
+struct LineInstance {
+    polyline: Option<Polyline>,
+    object_data: ObjectData,
+    // the number of quads of the line in the transform buffer
+    quads: usize,
+    // the quads need to be created again
+    dirty: bool,
+}
+
+pub struct Line {
+    program: Program,
+    vertex_array: VertexArray,
+    model_buffer: Buffer, // the buffer needs to stay alive
+    transform_buffer: InstanceBuffer<TransformData>, // the buffer needs to stay alive
+    lines: Vec<LineInstance>,
+    state: ObjectState,
+}
+
+impl Line {
+    // creates an empty Line
+    pub fn new() -> Self {
+        Self {
+            program: Program::default(),
+            vertex_array: VertexArray::default(),
+            model_buffer: Buffer::default(),
+            transform_buffer: InstanceBuffer::new(),
+            lines: vec![],
+            state: ObjectState::Ok,
+        }
+    }
+
+    // send the quads of the lines that changed to the transform buffer,
+    // every line keeps its range of quads so only that range changes
+    fn update(&mut self) {
+        let mut start = 0;
+        for line in &mut self.lines {
+            if line.dirty {
+                let transform_data = line.transform_data();
+                self.transform_buffer.splice(start, start + line.quads, &transform_data);
+                line.quads = transform_data.len();
+                line.dirty = false;
+            }
+
+            start += line.quads;
+        }
+    }
+
+    // take the quads of line i out of the transform buffer,
+    // they get created again on the next update
+    fn clear_quads(&mut self, i: usize) {
+        let start: usize = self.lines[..i].iter().map(|line| line.quads).sum();
+        let line = &mut self.lines[i];
+        self.transform_buffer.splice(start, start + line.quads, &[]);
+        line.quads = 0;
+        line.dirty = true;
+    }
+}
+
+impl LineInstance {
+    // a line that still needs to create its quads
+    fn new(polyline: Option<Polyline>, object_data: ObjectData) -> Self {
+        Self {
+            polyline,
+            object_data,
+            quads: 0,
+            dirty: true,
+        }
+    }
+
+    // create the transform data of all quads of the line
+    fn transform_data(&self) -> Vec<TransformData> {
+        let mut transform_data = vec![];
+        let object_data = &self.object_data;
+        let (x_offset, y_offset) = object_data.offset;
+
+        let quads = match &self.polyline {
+            Some(polyline) => polyline.quads(),
+            None => {
+                let (width, height) = object_data.dim;
+                Polyline::line(0.0, 0.0, width, height, 1.0, &object_data.color).quads()
+            }
+        };
+
+        for quad in quads {
+            let c = quad.corners;
+            let l = quad.local;
+            let color = quad.color;
+            let (dash, gap) = match &self.polyline {
+                Some(Polyline { dash: Some(dash), .. }) => *dash,
+                _ => (0.0, 0.0),
+            };
+            let kind = if quad.disc { 1.0 } else { 0.0 };
+
+            let quad_data: TransformData = [
+                c[0].0 + x_offset, c[0].1 + y_offset, c[1].0 + x_offset, c[1].1 + y_offset,
+                c[2].0 + x_offset, c[2].1 + y_offset, c[3].0 + x_offset, c[3].1 + y_offset,
+                l[0].0, l[0].1, l[1].0, l[1].1, l[2].0, l[2].1, l[3].0, l[3].1,
+                color.r, color.g, color.b, color.a * object_data.opacity,
+                kind, dash, gap, 0.0,
+            ];
+
+            transform_data.push(quad_data);
+        }
+
+        transform_data
+    }
+}
+
+impl Default for Line {
+    // an empty Line
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Object for Line {
+    // add an new Line from the offset to offset + dim
+    fn add(&mut self, object_data: &ObjectData) {
+        self.lines.push(LineInstance::new(None, *object_data));
+    }
+
+    /// set the component data
+    /// for a specific line 'i', a new color recolors all segments
+    fn set(&mut self, i: usize, object_data: &ObjectData) {
+        let line = &mut self.lines[i];
+        let old = line.object_data.color;
+        let new = object_data.color;
+        let recolor = (old.r, old.g, old.b, old.a) != (new.r, new.g, new.b, new.a);
+
+        if let (Some(polyline), true) = (&mut line.polyline, recolor) {
+            polyline.set_color(&new);
+        }
+
+        line.object_data = *object_data;
+        line.dirty = true;
+    }
+
+    // removes a line from
+    // the transform data
+    fn remove(&mut self, i: usize) {
+        self.clear_quads(i);
+        self.lines.remove(i);
+    }
+
+    // removes a line by moving
+    // the last one into its place
+    fn swap_remove(&mut self, i: usize) {
+        // the last line gets created again at its new place
+        self.clear_quads(self.lines.len() - 1);
+        self.clear_quads(i);
+        self.lines.swap_remove(i);
+    }
+
+    // removes all lines from
+    // the transform data
+    fn remove_all(&mut self) {
+        self.lines = vec![];
+        self.transform_buffer.clear();
+    }
+
+    // create shaders and buffers
+    fn load(&mut self) -> Result<(), String> {
+        // the index of the corner of every quad
+        let model_data: [f32; 4] = [0.0, 1.0, 2.0, 3.0];
+
+        self.update();
+
+        unsafe {
+            // create the shaderprogram
+            let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
+            let fragment_shader = Shader::new(FRAGMENT_SHADER_SOURCE, gl::FRAGMENT_SHADER)?;
+            self.program = Program::new(&vertex_shader, &fragment_shader)?;
+
+            // create a new buffer for our vertex array (model + transform data)
+            self.vertex_array = VertexArray::new();
+            self.vertex_array.bind();
+
+            // create a new buffer for our model data
+            self.model_buffer = Buffer::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW);
+            self.model_buffer.set_data(&model_data.to_vec());
+            // and create the attributes in the vertex shader
+            gl::VertexAttribPointer(0, 1, gl::FLOAT, gl::FALSE, 4, std::ptr::null()); // corner
+            gl::EnableVertexAttribArray(0);
+
+            // create a new buffer for our transform data
+            self.transform_buffer.load();
+            // and create the attributes in the vertex shader
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, 96, std::ptr::null()); // position_1
+            gl::VertexAttribPointer(2, 4, gl::FLOAT, gl::FALSE, 96, 16 as *const _); // position_2
+            gl::VertexAttribPointer(3, 4, gl::FLOAT, gl::FALSE, 96, 32 as *const _); // local_1
+            gl::VertexAttribPointer(4, 4, gl::FLOAT, gl::FALSE, 96, 48 as *const _); // local_2
+            gl::VertexAttribPointer(5, 4, gl::FLOAT, gl::FALSE, 96, 64 as *const _); // color
+            gl::VertexAttribPointer(6, 4, gl::FLOAT, gl::FALSE, 96, 80 as *const _); // params
+            for attribute in 1..7 {
+                gl::VertexAttribDivisor(attribute, 1);
+                gl::EnableVertexAttribArray(attribute);
+            }
+        }
+
+        self.state = ObjectState::Ok;
+        Ok(())
+    }
+
+    // uploads the transformation data that changed
+    fn reload(&mut self) {
+        self.update();
+        self.transform_buffer.upload();
+        self.state = ObjectState::Ok;
+    }
+
+    // draw the lines to the screen
+    fn draw(&mut self, draw: &Draw, camera: &Transform, model_transform: &Transform) -> Result<(), String> {
+        // reset the transformation data if needed
+        match self.state {
+            ObjectState::Reload => self.reload(),
+            ObjectState::Ok => (),
+        }
+
+        // create the mvp (model view projection) matrixes
+        let projection = mvp::ortho(&draw.window);
+        let view = camera.matrix();
+        let model = model_transform.matrix();
+
+        unsafe {
+            // bind the programm and vertex array before sending
+            // uniform and drawing
+            self.program.bind();
+            self.vertex_array.bind();
+
+            // set the model view matrices
+            let projection_location = self.program.get_uniform_location("projection")?;
+            let view_location = self.program.get_uniform_location("view")?;
+            let model_location = self.program.get_uniform_location("model")?;
+            gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
+            gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
+            gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
+            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_buffer.len() as i32);
+        }
+
+        Ok(())
+    }
+
+    // set the state of the Object
+    fn set_state(&mut self, object_state: ObjectState) {
+        self.state = object_state;
+    }
+
+    // add a new polyline to the lines
+    fn add_polyline(&mut self, polyline: &Polyline) -> Result<(), String> {
+        let mut object_data = ObjectData::default();
+        if let Some(color) = polyline.colors.first() {
+            object_data.color = *color;
+        }
+
+        self.lines.push(LineInstance::new(Some(polyline.clone()), object_data));
+
+        Ok(())
+    }
+
+    // replace the polyline of line i
+    fn set_polyline(&mut self, i: usize, polyline: &Polyline) -> Result<(), String> {
+        match self.lines.get_mut(i) {
+            Some(line) => {
+                line.polyline = Some(polyline.clone());
+                line.dirty = true;
+                Ok(())
+            }
+            None => Err(format!("Line with index '{}' not found.", i)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // round a point to 3 decimals
+    fn round(point: (f32, f32)) -> (f32, f32) {
+        ((point.0 * 1000.0).round() / 1000.0, (point.1 * 1000.0).round() / 1000.0)
+    }
+
+    #[test]
+    fn test_butt_line() {
+        let polyline = Polyline::line(0.0, 0.0, 10.0, 0.0, 2.0, &Color::grey(255));
+        let quads = polyline.quads();
+        assert_eq!(quads.len(), 1);
+        assert_eq!(quads[0].corners, [(0.0, 1.0), (0.0, -1.0), (10.0, -1.0), (10.0, 1.0)]);
+        assert_eq!(quads[0].local[2], (10.0, -1.0));
+    }
+
+    #[test]
+    fn test_caps() {
+        let mut polyline = Polyline::line(0.0, 0.0, 10.0, 0.0, 2.0, &Color::grey(255));
+        polyline.cap = LineCap::Square;
+        let quads = polyline.quads();
+        assert_eq!(quads[0].corners[0], (-1.0, 1.0));
+        assert_eq!(quads[0].corners[2], (11.0, -1.0));
+
+        polyline.cap = LineCap::Round;
+        let quads = polyline.quads();
+        assert_eq!(quads.len(), 3);
+        assert_eq!(quads.iter().filter(|quad| quad.disc).count(), 2);
+    }
+
+    #[test]
+    fn test_joins() {
+        let points = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+        let mut polyline = Polyline::new(&points, 2.0, &Color::grey(255));
+
+        // the miter corners are shared by both segments
+        let quads = polyline.quads();
+        assert_eq!(quads.len(), 2);
+        assert_eq!(round(quads[0].corners[3]), (9.0, 1.0));
+        assert_eq!(round(quads[1].corners[0]), (9.0, 1.0));
+        assert_eq!(round(quads[0].corners[2]), (11.0, -1.0));
+
+        polyline.join = LineJoin::Bevel;
+        assert_eq!(polyline.quads().len(), 3);
+
+        polyline.join = LineJoin::Round;
+        let quads = polyline.quads();
+        assert_eq!(quads.len(), 3);
+        assert!(quads[1].disc);
+
+        // sharp turns fall back to a bevel
+        let mut polyline = Polyline::new(&[(0.0, 0.0), (10.0, 0.0), (0.0, 0.5)], 2.0, &Color::grey(255));
+        polyline.miter_limit = 2.0;
+        assert_eq!(polyline.quads().len(), 3);
+    }
+
+    #[test]
+    fn test_dashes() {
+        let points = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)];
+        let mut polyline = Polyline::new(&points, 2.0, &Color::grey(255));
+        polyline.join = LineJoin::Round;
+        polyline.cap = LineCap::Round;
+
+        // the join at 10 and the start are in a dash, the end at 20 is in a gap
+        polyline.dash = Some((3.0, 5.0));
+        let quads = polyline.quads();
+        assert_eq!(quads.iter().filter(|quad| quad.disc).count(), 2);
+        assert_eq!(quads.iter().filter(|quad| !quad.disc).count(), 2);
+
+        // only the start is in a dash
+        polyline.dash = Some((1.0, 7.0));
+        let quads = polyline.quads();
+        assert_eq!(quads.iter().filter(|quad| quad.disc).count(), 1);
+        assert_eq!(quads[2].corners[0], (-1.0, -1.0));
+    }
+
+    #[test]
+    fn test_segments() {
+        let mut polyline = Polyline::new(&[(0.0, 0.0), (5.0, 5.0), (5.0, 5.0)], 1.0, &Color::grey(0));
+        assert_eq!(polyline.segments(), 2);
+        assert!(polyline.set_segment_thickness(1, 4.0).is_ok());
+        assert!(polyline.set_segment_color(2, &Color::grey(10)).is_err());
+        // segments without length are not drawn
+        assert_eq!(polyline.quads().len(), 1);
+
+        let path = Polyline::path(&[(0, 0), (1, 0)], 32.0, 2.0, &Color::grey(0));
+        assert_eq!(path.points, vec![(16.0, 16.0), (48.0, 16.0)]);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::core::shader::object::{Object, ObjectState, TextureCoordinate, TextureOptions};
+use crate::core::shader::object::texture::Texture;
+use crate::core::resource::image::Image;
+use crate::core::shader::data::ObjectData;
+use crate::core::project::Draw;
+use crate::core::math::transform::Transform;
+
+/// how the edges or the center of a nine slice fill their space
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum SliceMode {
+    // scale the part of the image to the space
+    #[default]
+    Stretch,
+    // repeat the part of the image, the last one gets cut off
+    Tile,
+}
+
+/// The borders of a nine slice image,
+/// the corners are never scaled
+/// ```rust
+/// let slices = NineSliceData {
+///     insets: (8.0, 8.0, 8.0, 8.0),
+///     edges: SliceMode::Tile,
+///     ..NineSliceData::default()
+/// };
+///
+/// let mut panel = Shader::nine_slice(&image, &slices).unwrap();
+/// panel.set_dim(300.0, 200.0);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct NineSliceData {
+    // left, top, right and bottom border in pixels of the image
+    pub insets: (f32, f32, f32, f32),
+    pub edges: SliceMode,
+    pub center: SliceMode,
+}
+
+// a part of an axis: position, length,
+// start and length in the image
+type Segment = (f32, f32, f32, f32);
+
+/*
+A NineSlice draws every instance as up to nine
+parts of a texture so the borders keep their size
+at every dim
+
+the parts are instances of a texture object
+that gets rebuilt when the instances change
+*/
+pub struct NineSlice {
+    texture: Texture,
+    slices: NineSliceData,
+    // the part of the texture that is used,
+    // left, top, right and bottom in uv
+    region: (f32, f32, f32, f32),
+    // the size of the region in pixels
+    size: (f32, f32),
+    instances: Vec<ObjectData>,
+    state: ObjectState,
+}
+
+impl NineSlice {
+    // creates an empty NineSlice of the whole image
+    pub fn new(image: &Image, slices: &NineSliceData, options: &TextureOptions) -> Self {
+        Self::region(image, (0.0, 1.0, 1.0, 0.0), slices, options)
+    }
+
+    // creates an empty NineSlice of a part of the image in uv,
+    // e.g. a tile of a tilemap
+    pub fn region(image: &Image, region: (f32, f32, f32, f32), slices: &NineSliceData, options: &TextureOptions) -> Self {
+        let (left, top, right, bottom) = region;
+        Self {
+            texture: Texture::with_options(image, options),
+            slices: *slices,
+            region,
+            size: ((right - left).abs() * image.width, (top - bottom).abs() * image.height),
+            instances: vec![],
+            state: ObjectState::Reload,
+        }
+    }
+
+    // the parts of all instances as texture instances
+    fn build(&self) -> Vec<ObjectData> {
+        self.instances
+            .iter()
+            .flat_map(|object_data| slice(object_data, &self.slices, self.region, self.size))
+            .collect()
+    }
+}
+
+impl Object for NineSlice {
+    // add a new nine slice
+    fn add(&mut self, object_data: &ObjectData) {
+        self.instances.push(*object_data);
+    }
+
+    // set the component data of nine slice i
+    fn set(&mut self, i: usize, object_data: &ObjectData) {
+        if let Some(instance) = self.instances.get_mut(i) {
+            *instance = *object_data;
+        }
+    }
+
+    // load the texture
+    fn load(&mut self) -> Result<(), String> {
+        self.texture.load()?;
+        self.state = ObjectState::Reload;
+        Ok(())
+    }
+
+    // rebuild the parts and upload them
+    fn reload(&mut self) {
+        let parts = self.build();
+        self.texture.remove_all();
+        for part in &parts {
+            self.texture.add(part);
+        }
+        self.texture.reload();
+        self.state = ObjectState::Ok;
+    }
+
+    // removes nine slice i
+    fn remove(&mut self, i: usize) {
+        self.instances.remove(i);
+    }
+
+    // removes nine slice i by moving
+    // the last one into its place
+    fn swap_remove(&mut self, i: usize) {
+        self.instances.swap_remove(i);
+    }
+
+    // removes all nine slices
+    fn remove_all(&mut self) {
+        self.instances.clear();
+    }
+
+    fn draw(&mut self, draw: &Draw, camera: &Transform, model_transform: &Transform) -> Result<(), String> {
+        // rebuild the parts if needed
+        match self.state {
+            ObjectState::Reload => self.reload(),
+            ObjectState::Ok => (),
+        }
+
+        self.texture.draw(draw, camera, model_transform)
+    }
+
+    // set the state of the Object
+    fn set_state(&mut self, object_state: ObjectState) {
+        self.state = object_state;
+    }
+
+    // replace the image, the region stays the same
+    fn set_image(&mut self, image: &Image) -> Result<(), String> {
+        let (left, top, right, bottom) = self.region;
+        self.size = ((right - left).abs() * image.width, (top - bottom).abs() * image.height);
+        self.state = ObjectState::Reload;
+        self.texture.set_image(image)
+    }
+
+    // replace a part of the image starting at x and y from the top left,
+    // the size stays the same so the parts stay the same
+    fn set_sub_image(&mut self, x: u32, y: u32, image: &Image) -> Result<(), String> {
+        self.texture.set_sub_image(x, y, image)
+    }
+
+    // change how the texture is sampled
+    fn set_texture_options(&mut self, options: &TextureOptions) -> Result<(), String> {
+        self.texture.set_texture_options(options)
+    }
+}
+
+// the parts of a single nine slice
+fn slice(object_data: &ObjectData, slices: &NineSliceData, region: (f32, f32, f32, f32), size: (f32, f32)) -> Vec<ObjectData> {
+    let (width, height) = object_data.dim;
+    let (left, top, right, bottom) = slices.insets;
+    let (pivot_x, pivot_y) = object_data.pivot_position();
+
+    let (borders_x, edges_x) = segments(width, size.0, left, right, slices.edges);
+    let (borders_y, edges_y) = segments(height, size.1, top, bottom, slices.edges);
+    let (_, center_x) = segments(width, size.0, left, right, slices.center);
+    let (_, center_y) = segments(height, size.1, top, bottom, slices.center);
+
+    // the corners, the top and bottom edge,
+    // the left and right edge and the center
+    let areas = [
+        (&borders_x, &borders_y),
+        (&edges_x, &borders_y),
+        (&borders_x, &edges_y),
+        (&center_x, &center_y),
+    ];
+
+    let mut parts = vec![];
+    for (columns, rows) in areas {
+        for &(x, part_width, image_x, image_width) in columns {
+            for &(y, part_height, image_y, image_height) in rows {
+                // flipping mirrors where the parts are and the parts themselves
+                let x = if object_data.flip_x { width - x - part_width } else { x };
+                let y = if object_data.flip_y { height - y - part_height } else { y };
+
+                parts.push(ObjectData {
+                    offset: (object_data.offset.0 + x, object_data.offset.1 + y),
+                    dim: (part_width, part_height),
+                    texcoord: texcoord(region, size, (image_x, image_y, image_width, image_height)),
+                    // every part turns around the pivot of the whole nine slice
+                    pivot: ((pivot_x - x) / part_width, (pivot_y - y) / part_height),
+                    ..*object_data
+                });
+            }
+        }
+    }
+
+    parts
+}
+
+// the border segments and the middle segments of an axis of length,
+// the image is size long and has borders of start and end
+fn segments(length: f32, size: f32, start: f32, end: f32, mode: SliceMode) -> (Vec<Segment>, Vec<Segment>) {
+    // borders that don't fit get smaller
+    let scale = if start + end > length { length / (start + end) } else { 1.0 };
+    let (start_length, end_length) = (start * scale, end * scale);
+    let middle_length = length - start_length - end_length;
+    let image_middle = size - start - end;
+
+    let mut borders = vec![(0.0, start_length, 0.0, start), (length - end_length, end_length, size - end, end)];
+    borders.retain(|segment| segment.1 > 0.0);
+
+    let mut middle = vec![];
+    if middle_length > 0.0 && image_middle > 0.0 {
+        match mode {
+            SliceMode::Stretch => middle.push((start_length, middle_length, start, image_middle)),
+            SliceMode::Tile => {
+                let mut position = 0.0;
+                while position < middle_length {
+                    let part = image_middle.min(middle_length - position);
+                    middle.push((start_length + position, part, start, part));
+                    position += image_middle;
+                }
+            }
+        }
+    }
+
+    (borders, middle)
+}
+
+// the texture coordinate of a part of the region in pixels from the top left
+fn texcoord(region: (f32, f32, f32, f32), size: (f32, f32), part: (f32, f32, f32, f32)) -> TextureCoordinate {
+    let (left, top, right, bottom) = region;
+    let (x, y, width, height) = part;
+
+    let u1 = left + x / size.0 * (right - left);
+    let u2 = left + (x + width) / size.0 * (right - left);
+    let v1 = top + y / size.1 * (bottom - top);
+    let v2 = top + (y + height) / size.1 * (bottom - top);
+
+    [
+        u1, v2,
+        u1, v1,
+        u2, v1,
+        u2, v2,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let (borders, middle) = segments(100.0, 32.0, 8.0, 8.0, SliceMode::Stretch);
+        assert_eq!(vec![(0.0, 8.0, 0.0, 8.0), (92.0, 8.0, 24.0, 8.0)], borders);
+        assert_eq!(vec![(8.0, 84.0, 8.0, 16.0)], middle);
+
+        // 84 = 5 * 16 + 4
+        let (_, tiled) = segments(100.0, 32.0, 8.0, 8.0, SliceMode::Tile);
+        assert_eq!(6, tiled.len());
+        assert_eq!((88.0, 4.0, 8.0, 4.0), tiled[5]);
+
+        // too small for the borders
+        let (borders, middle) = segments(8.0, 32.0, 8.0, 8.0, SliceMode::Stretch);
+        assert_eq!(vec![(0.0, 4.0, 0.0, 8.0), (4.0, 4.0, 24.0, 8.0)], borders);
+        assert!(middle.is_empty());
+    }
+
+    #[test]
+    fn test_slice() {
+        let slices = NineSliceData {
+            insets: (8.0, 8.0, 8.0, 8.0),
+            ..NineSliceData::default()
+        };
+        let object_data = ObjectData {
+            offset: (10.0, 20.0),
+            dim: (100.0, 50.0),
+            ..ObjectData::default()
+        };
+
+        let parts = slice(&object_data, &slices, (0.0, 1.0, 1.0, 0.0), (32.0, 32.0));
+        assert_eq!(9, parts.len());
+        // the top left corner keeps its size
+        assert_eq!((10.0, 20.0), parts[0].offset);
+        assert_eq!((8.0, 8.0), parts[0].dim);
+        assert_eq!([0.0, 0.75, 0.0, 1.0, 0.25, 1.0, 0.25, 0.75], parts[0].texcoord);
+        // all parts cover the whole nine slice
+        let area: f32 = parts.iter().map(|part| part.dim.0 * part.dim.1).sum();
+        assert_eq!(5000.0, area);
+
+        let tiled = NineSliceData {
+            center: SliceMode::Tile,
+            ..slices
+        };
+        // 84 x 34 in tiles of 16 are 6 x 3 center parts
+        assert_eq!(8 + 18, slice(&object_data, &tiled, (0.0, 1.0, 1.0, 0.0), (32.0, 32.0)).len());
+    }
+}
@@ -0,0 +1,498 @@
+use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, TextureBuffer, TextureOptions};
+use crate::core::shader::program::Program;
+use crate::core::shader::data::ObjectData;
+use crate::core::resource::image::Image;
+use crate::core::math::triangulate::triangulate;
+use crate::core::math::mvp;
+use crate::core::math::culling::{self, Bounds};
+use crate::core::project::Draw;
+use crate::core::math::transform::Transform;
+use crate::core::color::Color;
+use crate::core::shader::Shader;
+
+const VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in vec2 position;
+    layout (location = 1) in vec4 color;
+    layout (location = 2) in vec2 texcoord;
+
+    uniform mat4 projection;
+    uniform mat4 view;
+    uniform mat4 model;
+
+    out vec4 oColor;
+    out vec2 oTexCoord;
+
+    void main() {
+        gl_Position = projection * view * model * vec4(position, 0.0, 1.0);
+        oColor = color;
+        oTexCoord = texcoord;
+    }
+"#;
+
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+    uniform int textured;
+
+    in vec4 oColor;
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        if(textured == 1) {
+            color = texture(sampler, oTexCoord) * oColor;
+        } else {
+            color = oColor;
+        }
+
+        // invisible pixels are not drawn so they don't count for stencil masks
+        if(color.a < 0.004) {
+            discard;
+        }
+    }
+"#;
+
+/// The points, colors and triangles of a polygon
+///
+/// concave outlines and holes are split into triangles
+/// ```rust
+/// let outline = [(0.0, 0.0), (200.0, 0.0), (200.0, 100.0), (100.0, 50.0), (0.0, 100.0)];
+/// let mut polygon = PolygonData::new(&outline, &Color::rgb(0, 200, 0)).unwrap();
+/// polygon.set_vertex_color(3, &Color::rgb(0, 0, 255)).unwrap();
+/// let mut shape = Shader::polygon(&polygon).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PolygonData {
+    points: Vec<(f32, f32)>,
+    colors: Vec<Color>,
+    uvs: Vec<(f32, f32)>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl PolygonData {
+    // create a polygon from its outline
+    pub fn new(outline: &[(f32, f32)], color: &Color) -> Result<Self, String> {
+        Self::with_holes(outline, &[], color)
+    }
+
+    // create a polygon from its outline
+    // with holes cut out of it
+    pub fn with_holes(outline: &[(f32, f32)], holes: &[Vec<(f32, f32)>], color: &Color) -> Result<Self, String> {
+        let triangles = triangulate(outline, holes)?;
+
+        let mut points = outline.to_vec();
+        for hole in holes {
+            points.extend_from_slice(hole);
+        }
+
+        Ok(Self {
+            colors: vec![*color; points.len()],
+            uvs: vec![],
+            points,
+            triangles,
+        })
+    }
+
+    // create a triangle mesh, every triangle
+    // holds 3 indices into the points
+    pub fn mesh(points: &[(f32, f32)], triangles: &[[usize; 3]], color: &Color) -> Result<Self, String> {
+        for triangle in triangles {
+            if triangle.iter().any(|i| *i >= points.len()) {
+                return Err(format!("Triangle {:?} points outside of the mesh.", triangle));
+            }
+        }
+
+        Ok(Self {
+            points: points.to_vec(),
+            colors: vec![*color; points.len()],
+            uvs: vec![],
+            triangles: triangles.to_vec(),
+        })
+    }
+
+    // get the points of the polygon,
+    // the outline followed by the holes
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    // get the triangles of the polygon
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    // get the colors of the points
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    // set the color of all points
+    pub fn set_color(&mut self, color: &Color) {
+        self.colors = vec![*color; self.points.len()];
+    }
+
+    // set the color of point i
+    pub fn set_vertex_color(&mut self, i: usize, color: &Color) -> Result<(), String> {
+        self.index_oob(i)?;
+        self.colors[i] = *color;
+        Ok(())
+    }
+
+    // set the colors of all points at once
+    pub fn set_colors(&mut self, colors: &[Color]) -> Result<(), String> {
+        if colors.len() != self.points.len() {
+            return Err(format!("Expected {} colors but got {}.", self.points.len(), colors.len()));
+        }
+
+        self.colors = colors.to_vec();
+        Ok(())
+    }
+
+    // set the texture coordinates of all points, 0, 0 is the
+    // top left of the image like its pixels,
+    // without them the texture is stretched over the polygon
+    pub fn set_uvs(&mut self, uvs: &[(f32, f32)]) -> Result<(), String> {
+        if uvs.len() != self.points.len() {
+            return Err(format!("Expected {} texture coordinates but got {}.", self.points.len(), uvs.len()));
+        }
+
+        self.uvs = uvs.to_vec();
+        Ok(())
+    }
+
+    // the rectangle around the points
+    pub fn bounds(&self) -> Bounds {
+        culling::point_bounds(&self.points)
+    }
+
+    // move all points by x and y
+    pub(crate) fn translate(&mut self, x: f32, y: f32) {
+        for point in &mut self.points {
+            *point = (point.0 + x, point.1 + y);
+        }
+    }
+
+    // the vertices of all triangles
+    // (x, y, r, g, b, a, u, v)
+    fn vertices(&self, object_data: &ObjectData) -> Vec<VertexData> {
+        let (x_offset, y_offset) = object_data.offset;
+        let uvs = if self.uvs.is_empty() { self.stretched_uvs() } else { self.uvs.clone() };
+
+        let mut vertices = Vec::with_capacity(self.triangles.len() * 3);
+        for triangle in &self.triangles {
+            for &i in triangle {
+                let (x, y) = self.points[i];
+                let color = self.colors[i];
+                let (u, v) = uvs[i];
+                // images are upside down for opengl
+                vertices.push([
+                    x + x_offset, y + y_offset,
+                    color.r, color.g, color.b, color.a * object_data.opacity,
+                    u, 1.0 - v,
+                ]);
+            }
+        }
+
+        vertices
+    }
+
+    // texture coordinates that stretch a texture
+    // over the bounding box of the polygon
+    fn stretched_uvs(&self) -> Vec<(f32, f32)> {
+        let (min_x, min_y, width, height) = self.bounds();
+        let width = width.max(f32::EPSILON);
+        let height = height.max(f32::EPSILON);
+
+        self.points
+            .iter()
+            .map(|(x, y)| ((x - min_x) / width, (y - min_y) / height))
+            .collect()
+    }
+
+    // checks if a point is in the polygon
+    fn index_oob(&self, i: usize) -> Result<(), String> {
+        if i >= self.points.len() {
+            Err(format!("Point with index '{}' not found.", i))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/*
+Polygon is an implementation of the shaders above
+it holds all the data that is used for drawing polygons
+and triangle meshes to the screen, all polygons are
+drawn in a single draw call
+
+when polygon gets dropped the shader also gets
+deletet from the graphics card
+*/
+type VertexData = [f32; 8];
+
+// a polygon and the object data it was added with,
+// polygons added with object data are rectangles of size dim
+struct PolygonInstance {
+    polygon: Option<PolygonData>,
+    object_data: ObjectData,
+}
+
+pub struct Polygon {
+    program: Program,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer, // the buffer needs to stay alive
+    texture_buffer: TextureBuffer, // the buffer needs to stay alive
+    vertex_data: Vec<VertexData>,
+    polygons: Vec<PolygonInstance>,
+    image: Option<Image>,
+    state: ObjectState,
+}
+
+impl Polygon {
+    // creates an empty Polygon
+    pub fn new() -> Self {
+        Self {
+            program: Program::default(),
+            vertex_array: VertexArray::default(),
+            vertex_buffer: Buffer::default(),
+            texture_buffer: TextureBuffer::default(),
+            vertex_data: vec![],
+            polygons: vec![],
+            image: None,
+            state: ObjectState::Ok,
+        }
+    }
+
+    // creates an empty Polygon
+    // that is filled with an image
+    pub fn textured(image: &Image) -> Self {
+        let mut polygon = Self::new();
+        polygon.image = Some(image.clone());
+        polygon
+    }
+
+    // create the vertex data of all polygons
+    fn build(&mut self) {
+        self.vertex_data = vec![];
+
+        for instance in &self.polygons {
+            let object_data = &instance.object_data;
+
+            match &instance.polygon {
+                Some(polygon) => self.vertex_data.extend(polygon.vertices(object_data)),
+                None => {
+                    let (width, height) = object_data.dim;
+                    let outline = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)];
+                    let triangles = [[0, 1, 2], [0, 2, 3]];
+                    if let Ok(rect) = PolygonData::mesh(&outline, &triangles, &object_data.color) {
+                        self.vertex_data.extend(rect.vertices(object_data));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for Polygon {
+    // an empty Polygon
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Object for Polygon {
+    // add an new rectangle with the size of dim
+    fn add(&mut self, object_data: &ObjectData) {
+        self.polygons.push(PolygonInstance {
+            polygon: None,
+            object_data: *object_data,
+        });
+    }
+
+    /// set the component data
+    /// for a specific polygon 'i', a new color recolors all points
+    fn set(&mut self, i: usize, object_data: &ObjectData) {
+        let instance = &mut self.polygons[i];
+        let old = instance.object_data.color;
+        let new = object_data.color;
+        let recolor = (old.r, old.g, old.b, old.a) != (new.r, new.g, new.b, new.a);
+
+        if let (Some(polygon), true) = (&mut instance.polygon, recolor) {
+            polygon.set_color(&new);
+        }
+
+        instance.object_data = *object_data;
+    }
+
+    // removes a polygon from
+    // the vertex data
+    fn remove(&mut self, i: usize) {
+        self.polygons.remove(i);
+    }
+
+    // removes a polygon by moving
+    // the last one into its place
+    fn swap_remove(&mut self, i: usize) {
+        self.polygons.swap_remove(i);
+    }
+
+    // removes all polygons from
+    // the vertex data
+    fn remove_all(&mut self) {
+        self.polygons = vec![];
+    }
+
+    // create shaders and buffers
+    fn load(&mut self) -> Result<(), String> {
+        self.build();
+        let vertex_data = self.vertex_data.concat();
+
+        unsafe {
+            // create the shaderprogram
+            let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
+            let fragment_shader = Shader::new(FRAGMENT_SHADER_SOURCE, gl::FRAGMENT_SHADER)?;
+            self.program = Program::new(&vertex_shader, &fragment_shader)?;
+
+            // create a new buffer for our vertex array
+            self.vertex_array = VertexArray::new();
+            self.vertex_array.bind();
+
+            // create the texture buffer out of the image
+            if let Some(image) = &self.image {
+                self.texture_buffer = TextureBuffer::new();
+                self.texture_buffer.set_data(&image.to_rgba_image(), &TextureOptions::default());
+            }
+
+            // create a new buffer for the vertices of all triangles
+            self.vertex_buffer = Buffer::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW);
+            self.vertex_buffer.set_data(&vertex_data);
+            // and create the attributes in the vertex shader
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 32, std::ptr::null()); // position
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, 32, 8 as *const _); // color
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 32, 24 as *const _); // texcoord
+            gl::EnableVertexAttribArray(0);
+            gl::EnableVertexAttribArray(1);
+            gl::EnableVertexAttribArray(2);
+        }
+
+        self.state = ObjectState::Ok;
+        Ok(())
+    }
+
+    // resets the vertex data
+    fn reload(&mut self) {
+        self.build();
+        let vertex_data = self.vertex_data.concat();
+        self.vertex_buffer.set_data(&vertex_data);
+        self.state = ObjectState::Ok;
+    }
+
+    // draw the polygons to the screen
+    fn draw(&mut self, draw: &Draw, camera: &Transform, model_transform: &Transform) -> Result<(), String> {
+        // reset the vertex data if needed
+        match self.state {
+            ObjectState::Reload => self.reload(),
+            ObjectState::Ok => (),
+        }
+
+        // create the mvp (model view projection) matrixes
+        let projection = mvp::ortho(&draw.window);
+        let view = camera.matrix();
+        let model = model_transform.matrix();
+
+        unsafe {
+            // bind the programm and vertex array before sending
+            // uniform and drawing
+            // we also need to bind the texture buffer
+            self.program.bind();
+            self.vertex_array.bind();
+            self.texture_buffer.bind();
+
+            // set the model view matrices
+            let projection_location = self.program.get_uniform_location("projection")?;
+            let view_location = self.program.get_uniform_location("view")?;
+            let model_location = self.program.get_uniform_location("model")?;
+            let textured_location = self.program.get_uniform_location("textured")?;
+            gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
+            gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
+            gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
+            gl::Uniform1i(textured_location, self.image.is_some() as i32);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertex_data.len() as i32);
+        }
+
+        Ok(())
+    }
+
+    // set the state of the Object
+    fn set_state(&mut self, object_state: ObjectState) {
+        self.state = object_state;
+    }
+
+    // add a new polygon
+    fn add_polygon(&mut self, polygon: &PolygonData) -> Result<(), String> {
+        let mut object_data = ObjectData::default();
+        if let Some(color) = polygon.colors().first() {
+            object_data.color = *color;
+        }
+
+        self.polygons.push(PolygonInstance {
+            polygon: Some(polygon.clone()),
+            object_data,
+        });
+
+        Ok(())
+    }
+
+    // replace polygon i
+    fn set_polygon(&mut self, i: usize, polygon: &PolygonData) -> Result<(), String> {
+        match self.polygons.get_mut(i) {
+            Some(instance) => {
+                instance.polygon = Some(polygon.clone());
+                Ok(())
+            }
+            None => Err(format!("Polygon with index '{}' not found.", i)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polygon_data() {
+        let outline = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let polygon = PolygonData::new(&outline, &Color::grey(255)).unwrap();
+        assert_eq!(polygon.triangles().len(), 2);
+
+        // every triangle has its own 3 vertices
+        let object_data = ObjectData {
+            offset: (5.0, 5.0),
+            ..ObjectData::default()
+        };
+        let vertices = polygon.vertices(&object_data);
+        assert_eq!(vertices.len(), 6);
+        assert!(vertices.iter().any(|v| v[0] == 15.0 && v[1] == 15.0 && v[6] == 1.0 && v[7] == 0.0));
+
+        assert!(PolygonData::mesh(&outline, &[[0, 1, 4]], &Color::grey(0)).is_err());
+        let mut mesh = PolygonData::mesh(&outline, &[[0, 1, 2]], &Color::grey(0)).unwrap();
+        assert!(mesh.set_vertex_color(3, &Color::grey(10)).is_ok());
+        assert!(mesh.set_uvs(&[(0.0, 0.0)]).is_err());
+    }
+
+    #[test]
+    fn test_uvs() {
+        let outline = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let mut polygon = PolygonData::new(&outline, &Color::grey(255)).unwrap();
+        let stretched = polygon.vertices(&ObjectData::default());
+
+        // uvs from the top left of the image sample the same as the stretched texture
+        polygon.set_uvs(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]).unwrap();
+        let vertices = polygon.vertices(&ObjectData::default());
+        assert_eq!(stretched, vertices);
+        // the top left point is at the top of the opengl texture
+        assert!(vertices.iter().any(|v| v[0] == 0.0 && v[1] == 0.0 && v[6] == 0.0 && v[7] == 1.0));
+    }
+}
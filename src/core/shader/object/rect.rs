@@ -1,4 +1,4 @@
-use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object};
+use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, InstanceBuffer};
 use crate::core::shader::program::Program;
 use crate::core::shader::data::ObjectData;
 use crate::core::math::mvp;
@@ -12,29 +12,160 @@ const VERTEX_SHADER_SOURCE: &str = r#"
     layout (location = 1) in vec4 color;
     layout (location = 2) in vec2 offset;
     layout (location = 3) in vec2 scale;
+    layout (location = 4) in vec4 radius;
+    layout (location = 5) in vec4 stroke_color;
+    layout (location = 6) in vec4 shadow;
+    layout (location = 7) in vec4 shadow_color;
+    layout (location = 8) in vec4 gradient_color_1;
+    layout (location = 9) in vec4 gradient_color_2;
+    layout (location = 10) in vec4 gradient_color_3;
+    layout (location = 11) in vec4 gradient_color_4;
+    layout (location = 12) in vec4 gradient_positions;
+    layout (location = 13) in vec4 gradient;
+    layout (location = 14) in vec4 rotation;
     
     uniform mat4 projection;
     uniform mat4 view;
     uniform mat4 model;
 
     out vec4 oColor;
+    out vec2 oLocal;
+    out vec2 oSize;
+    out vec4 oRadius;
+    out vec4 oStrokeColor;
+    out vec4 oShadow;
+    out vec4 oShadowColor;
+    out vec4 oGradientColors[4];
+    out vec4 oGradientPositions;
+    out vec4 oGradient;
+
+    // mirror and rotate a point of the instance around its pivot,
+    // rotation holds the angle, the pivot as a fraction of size and the flip bits
+    vec2 rotate(vec2 local, vec2 size) {
+        int flip = int(rotation.w + 0.5);
+        vec2 mirrored = vec2(
+            (flip & 1) != 0 ? size.x - local.x : local.x,
+            (flip & 2) != 0 ? size.y - local.y : local.y
+        );
+        vec2 pivot = rotation.yz * size;
+        float s = sin(rotation.x);
+        float c = cos(rotation.x);
+        return mat2(c, s, -s, c) * (mirrored - pivot) + pivot;
+    }
+
+    // turn a direction on the screen into the space of the instance
+    vec2 unrotate(vec2 direction) {
+        int flip = int(rotation.w + 0.5);
+        float s = sin(rotation.x);
+        float c = cos(rotation.x);
+        vec2 d = mat2(c, -s, s, c) * direction;
+        return vec2((flip & 1) != 0 ? -d.x : d.x, (flip & 2) != 0 ? -d.y : d.y);
+    }
 
     void main() {
-        vec2 scale_position = position * scale;
-        vec2 offset_position = scale_position + offset;
+        // grow the rect so the shadow fits in
+        float margin = 0.0;
+        if(shadow_color.a > 0.0) {
+            margin = shadow.z + length(shadow.xy);
+        }
+
+        vec2 local = position * (scale + 2.0 * margin) - margin;
+        vec2 offset_position = rotate(local, scale) + offset;
         gl_Position = projection * view * model * vec4(offset_position, 0.0, 1.0);
         oColor = color;
+        oLocal = local;
+        oSize = scale;
+        oRadius = radius;
+        oStrokeColor = stroke_color;
+        // the shadow keeps its direction on the screen
+        oShadow = vec4(unrotate(shadow.xy), shadow.zw);
+        oShadowColor = shadow_color;
+        oGradientColors = vec4[4](gradient_color_1, gradient_color_2, gradient_color_3, gradient_color_4);
+        oGradientPositions = gradient_positions;
+        oGradient = gradient;
     }
 "#;
 
 const FRAGMENT_SHADER_SOURCE: &str = r#"
     #version 330
     in vec4 oColor;
+    in vec2 oLocal;
+    in vec2 oSize;
+    in vec4 oRadius;
+    in vec4 oStrokeColor;
+    in vec4 oShadow;
+    in vec4 oShadowColor;
+    in vec4 oGradientColors[4];
+    in vec4 oGradientPositions;
+    in vec4 oGradient;
 
     out vec4 color;
 
+    // the color of the gradient at uv from 0.0 to 1.0 over the shape
+    // gradient holds the kind (1 linear, 2 radial), the angle and the number of stops
+    vec4 gradient_color(vec2 uv) {
+        float t;
+        if(oGradient.x < 1.5) {
+            vec2 direction = vec2(cos(oGradient.y), sin(oGradient.y));
+            t = dot(uv - 0.5, direction) / (abs(direction.x) + abs(direction.y)) + 0.5;
+        } else {
+            t = length(uv - 0.5) * 2.0;
+        }
+        t = clamp(t, 0.0, 1.0);
+
+        vec4 result = oGradientColors[0];
+        int count = int(oGradient.z);
+        for(int i = 1; i < count; i++) {
+            float from = oGradientPositions[i - 1];
+            float to = oGradientPositions[i];
+            result = mix(result, oGradientColors[i], clamp((t - from) / max(to - from, 0.0001), 0.0, 1.0));
+        }
+
+        return result;
+    }
+
+    // the distance of p to the edge of the rect, negative inside
+    // radius holds the top left, top right, bottom right and bottom left corner
+    float rounded_rect(vec2 p, vec2 size, vec4 radius) {
+        vec2 half_size = size * 0.5;
+        vec2 q = p - half_size;
+        float r = q.x > 0.0 ? (q.y > 0.0 ? radius.z : radius.y) : (q.y > 0.0 ? radius.w : radius.x);
+        r = min(r, min(half_size.x, half_size.y));
+        vec2 d = abs(q) - half_size + r;
+        return min(max(d.x, d.y), 0.0) + length(max(d, 0.0)) - r;
+    }
+
     void main() {
-        color = vec4(oColor);
+        float dist = rounded_rect(oLocal, oSize, oRadius);
+        float aa = max(fwidth(dist), 0.0001) * 0.5;
+
+        // the fill and the outline on the inside of the edge
+        float stroke_width = oShadow.w;
+        float stroke = 0.0;
+        if(stroke_width > 0.0) {
+            stroke = smoothstep(-stroke_width - aa, -stroke_width + aa, dist);
+        }
+        vec4 fill = vec4(oColor.rgb, 1.0);
+        if(oGradient.x > 0.5) {
+            fill = gradient_color(oLocal / oSize);
+        }
+        vec4 shape = mix(fill, oStrokeColor, stroke);
+        shape.a *= 1.0 - smoothstep(-aa, aa, dist);
+
+        // the shadow is the same shape moved and blurred
+        float shadow_dist = rounded_rect(oLocal - oShadow.xy, oSize, oRadius);
+        float blur = max(oShadow.z, aa);
+        float shadow = (1.0 - smoothstep(-blur, blur, shadow_dist)) * oShadowColor.a;
+
+        // put the shape over the shadow
+        float alpha = shape.a + shadow * (1.0 - shape.a);
+        vec3 rgb = (shape.rgb * shape.a + oShadowColor.rgb * shadow * (1.0 - shape.a)) / max(alpha, 0.0001);
+        color = vec4(rgb, alpha * oColor.a);
+
+        // invisible pixels are not drawn so they don't count for stencil masks
+        if(color.a < 0.004) {
+            discard;
+        }
     }
 "#;
 
@@ -47,14 +178,13 @@ the screen
 when rect gets dropped the shader also gets 
 deletet from the graphics card
 */
-type TransformData = [f32; 8];
+type TransformData = [f32; 52];
 
 pub struct Rect {
     program: Program,
     vertex_array: VertexArray,
     model_buffer: Buffer, // the buffer needs to stay alive
-    transform_buffer: Buffer, // the buffer needs to stay alive
-    transform_data: Vec<TransformData>,
+    transform_buffer: InstanceBuffer<TransformData>, // the buffer needs to stay alive
     state: ObjectState,
 }
 
@@ -65,8 +195,7 @@ impl Rect {
             program: Program::default(),
             vertex_array: VertexArray::default(),
             model_buffer: Buffer::default(),
-            transform_buffer: Buffer::default(),
-            transform_data: vec![],
+            transform_buffer: InstanceBuffer::new(),
             state: ObjectState::Ok,
         }
     }
@@ -75,43 +204,31 @@ impl Rect {
 impl Object for Rect {
     // add an new Rect to the transform data
     fn add(&mut self, object_data: &ObjectData) {
-        let color = object_data.color;
-        let opacity = object_data.opacity;
-        let (offset_x, offset_y) = object_data.offset;
-        let (width, height) = object_data.dim;
-
-        let transform_data: TransformData = [
-            color.r, color.g, color.b, opacity, offset_x, offset_y, width, height, 
-        ];
-
-        self.transform_data.push(transform_data);
+        self.transform_buffer.push(transform_data(object_data));
     }
 
     /// set the component data
     /// for a specific element 'i' of the transform data vector
     fn set(&mut self, i: usize, object_data: &ObjectData) {
-        let color = object_data.color;
-        let opacity = object_data.opacity;
-        let (offset_x, offset_y) = object_data.offset;
-        let (width, height) = object_data.dim;
-
-        let transform_data: TransformData = [
-            color.r, color.g, color.b, opacity, offset_x, offset_y, width, height, 
-        ];
-
-        self.transform_data[i] = transform_data;
+        self.transform_buffer.set(i, transform_data(object_data));
     }
 
     // removes a rect from 
     // the transform data
     fn remove(&mut self, i: usize) {
-        self.transform_data.remove(i);
+        self.transform_buffer.remove(i);
+    }
+
+    // removes a rect by moving
+    // the last one into its place
+    fn swap_remove(&mut self, i: usize) {
+        self.transform_buffer.swap_remove(i);
     }
 
     // removes all rect from 
     // the transform data
     fn remove_all(&mut self) {
-        self.transform_data = vec![];
+        self.transform_buffer.clear();
     }
 
 
@@ -124,8 +241,6 @@ impl Object for Rect {
             1.0,  1.0,      // bottom right 3
         ];
 
-        let transform_data = self.transform_data.concat();
-
         unsafe {
             // create the shaderprogram
             let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
@@ -144,28 +259,35 @@ impl Object for Rect {
             gl::EnableVertexAttribArray(0);
           
             // create a new buffer for our transform data
-            self.transform_buffer = Buffer::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW);
-            self.transform_buffer.set_data(&transform_data);
+            self.transform_buffer.load();
             // and create the attributes in the vertex shader
-            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, 32, 0 as *const _); // color
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 32, 16 as *const _); // offset
-            gl::VertexAttribPointer(3, 2, gl::FLOAT, gl::FALSE, 32, 24 as *const _); // scale
-            gl::VertexAttribDivisor(1, 1);
-            gl::VertexAttribDivisor(2, 1);
-            gl::VertexAttribDivisor(3, 1);
-            gl::EnableVertexAttribArray(1);
-            gl::EnableVertexAttribArray(2);
-            gl::EnableVertexAttribArray(3);
+            gl::VertexAttribPointer(1, 4, gl::FLOAT, gl::FALSE, 208, 0 as *const _); // color
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 208, 16 as *const _); // offset
+            gl::VertexAttribPointer(3, 2, gl::FLOAT, gl::FALSE, 208, 24 as *const _); // scale
+            gl::VertexAttribPointer(4, 4, gl::FLOAT, gl::FALSE, 208, 32 as *const _); // radius
+            gl::VertexAttribPointer(5, 4, gl::FLOAT, gl::FALSE, 208, 48 as *const _); // stroke_color
+            gl::VertexAttribPointer(6, 4, gl::FLOAT, gl::FALSE, 208, 64 as *const _); // shadow
+            gl::VertexAttribPointer(7, 4, gl::FLOAT, gl::FALSE, 208, 80 as *const _); // shadow_color
+            gl::VertexAttribPointer(8, 4, gl::FLOAT, gl::FALSE, 208, 96 as *const _); // gradient_color_1
+            gl::VertexAttribPointer(9, 4, gl::FLOAT, gl::FALSE, 208, 112 as *const _); // gradient_color_2
+            gl::VertexAttribPointer(10, 4, gl::FLOAT, gl::FALSE, 208, 128 as *const _); // gradient_color_3
+            gl::VertexAttribPointer(11, 4, gl::FLOAT, gl::FALSE, 208, 144 as *const _); // gradient_color_4
+            gl::VertexAttribPointer(12, 4, gl::FLOAT, gl::FALSE, 208, 160 as *const _); // gradient_positions
+            gl::VertexAttribPointer(13, 4, gl::FLOAT, gl::FALSE, 208, 176 as *const _); // gradient
+            gl::VertexAttribPointer(14, 4, gl::FLOAT, gl::FALSE, 208, 192 as *const _); // rotation
+            for attribute in 1..15 {
+                gl::VertexAttribDivisor(attribute, 1);
+                gl::EnableVertexAttribArray(attribute);
+            }
         }
 
         self.state = ObjectState::Ok;
         Ok(())
     }
 
-    // resets the transformation data
+    // uploads the transformation data that changed
     fn reload(&mut self) {
-        let transform_data = self.transform_data.concat();
-        self.transform_buffer.set_data(&transform_data);
+        self.transform_buffer.upload();
         self.state = ObjectState::Ok;
     }
 
@@ -195,7 +317,7 @@ impl Object for Rect {
             gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
             gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
             gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
-            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_data.len() as i32);
+            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_buffer.draw_count() as i32);
         }
 
         Ok(())
@@ -205,4 +327,35 @@ impl Object for Rect {
     fn set_state(&mut self, object_state: ObjectState) {
         self.state = object_state;
     }
-}
\ No newline at end of file
+
+    // only draw the instances in visible, None draws all
+    fn set_visible(&mut self, visible: Option<Vec<usize>>) {
+        if self.transform_buffer.set_visible(visible) {
+            self.state = ObjectState::Reload;
+        }
+    }
+}
+
+// create the transform data of a single rect
+fn transform_data(object_data: &ObjectData) -> TransformData {
+    let color = object_data.color;
+    let opacity = object_data.opacity;
+    let (offset_x, offset_y) = object_data.offset;
+    let (width, height) = object_data.dim;
+    let radius = object_data.corner_radius;
+    let stroke = object_data.stroke_color;
+    let shadow = object_data.shadow;
+    let gradient = object_data.gradient.data();
+
+    let mut transform_data = [0.0; 52];
+    transform_data[..24].copy_from_slice(&[
+        color.r, color.g, color.b, opacity, offset_x, offset_y, width, height,
+        radius[0], radius[1], radius[2], radius[3],
+        stroke.r, stroke.g, stroke.b, stroke.a,
+        shadow.offset.0, shadow.offset.1, shadow.blur, object_data.stroke_width,
+        shadow.color.r, shadow.color.g, shadow.color.b, shadow.color.a,
+    ]);
+    transform_data[24..48].copy_from_slice(&gradient);
+    transform_data[48..].copy_from_slice(&object_data.rotation_data());
+    transform_data
+}
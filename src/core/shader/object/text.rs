@@ -1,4 +1,4 @@
-use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, TextureBuffer};
+use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, TextureBuffer, TextureOptions, InstanceBuffer};
 use crate::core::shader::program::Program;
 use crate::core::resource::image::Image;
 use crate::core::shader::data::ObjectData;
@@ -14,6 +14,7 @@ const VERTEX_SHADER_SOURCE: &str = r#"
     layout (location = 2) in vec2 offset;
     layout (location = 3) in vec2 scale;
     layout (location = 4) in vec4 color;
+    layout (location = 5) in vec4 rotation;
     
     uniform mat4 projection;
     uniform mat4 view;
@@ -21,10 +22,24 @@ const VERTEX_SHADER_SOURCE: &str = r#"
 
     out vec2 oTexCoord;
     out vec4 oColor;
-   
+
+    // mirror and rotate a point of the instance around its pivot,
+    // rotation holds the angle, the pivot as a fraction of size and the flip bits
+    vec2 rotate(vec2 local, vec2 size) {
+        int flip = int(rotation.w + 0.5);
+        vec2 mirrored = vec2(
+            (flip & 1) != 0 ? size.x - local.x : local.x,
+            (flip & 2) != 0 ? size.y - local.y : local.y
+        );
+        vec2 pivot = rotation.yz * size;
+        float s = sin(rotation.x);
+        float c = cos(rotation.x);
+        return mat2(c, s, -s, c) * (mirrored - pivot) + pivot;
+    }
+
     void main() {
         vec2 scale_position = position * scale;
-        vec2 offset_position = scale_position + offset;
+        vec2 offset_position = rotate(scale_position, scale) + offset;
         gl_Position = projection * view * model * vec4(offset_position, 0.0, 1.0);
         oTexCoord = texcoord;
         oColor = color;
@@ -48,18 +63,22 @@ const FRAGMENT_SHADER_SOURCE: &str = r#"
         t.b = oColor.b;
         t.a = t.a * oColor.a;
         color = t;
+
+        // invisible pixels are not drawn so they don't count for stencil masks
+        if(color.a < 0.004) {
+            discard;
+        }
     }
 "#;
 
-type TransformData = [f32; 8];
+type TransformData = [f32; 12];
 
 pub struct Text {
     program: Program,
     vertex_array: VertexArray,
     model_buffer: Buffer, // the buffer needs to stay alive
     texture_buffer: TextureBuffer, // the buffer needs to stay alive
-    transform_buffer: Buffer, // the buffer needs to stay alive
-    transform_data: Vec<TransformData>,
+    transform_buffer: InstanceBuffer<TransformData>, // the buffer needs to stay alive
     image_data: Image,
     state: ObjectState,
 }
@@ -71,8 +90,7 @@ impl Text {
             vertex_array: VertexArray::default(),
             model_buffer: Buffer::default(),
             texture_buffer: TextureBuffer::default(),
-            transform_buffer: Buffer::default(),
-            transform_data: vec![],
+            transform_buffer: InstanceBuffer::new(),
             image_data: image.clone(),
             state: ObjectState::Ok,
         };
@@ -84,43 +102,31 @@ impl Text {
 impl Object for Text {
     // add an new Text to the transform data
     fn add(&mut self, object_data: &ObjectData) {
-        let (x_offset, y_offset) = object_data.offset;
-        let (width, height) = object_data.dim;
-        let color = object_data.color; 
-        let opacity = object_data.opacity;
-
-        let transform_data: TransformData = [
-            x_offset, y_offset, width, height, color.r, color.g, color.b, opacity
-        ];
-
-        self.transform_data.push(transform_data);
+        self.transform_buffer.push(transform_data(object_data));
     }
 
     /// set the component data
     /// for a specific element 'i' of the transform data vector
     fn set(&mut self, i: usize, object_data: &ObjectData) {
-        let (x_offset, y_offset) = object_data.offset;
-        let (width, height) = object_data.dim;
-        let color = object_data.color; 
-        let opacity = object_data.opacity;
-
-        let transform_data: TransformData = [
-            x_offset, y_offset, width, height, color.r, color.g, color.b, opacity
-        ];
-
-        self.transform_data[i] = transform_data;
+        self.transform_buffer.set(i, transform_data(object_data));
     }
 
     // removes a text from 
     // the transform data
     fn remove(&mut self, i: usize) {
-        self.transform_data.remove(i);
+        self.transform_buffer.remove(i);
+    }
+
+    // removes a text by moving
+    // the last one into its place
+    fn swap_remove(&mut self, i: usize) {
+        self.transform_buffer.swap_remove(i);
     }
 
     // removes all rect from 
     // the transform data
     fn remove_all(&mut self) {
-        self.transform_data = vec![];
+        self.transform_buffer.clear();
     }
 
     // load the shaders 
@@ -133,8 +139,6 @@ impl Object for Text {
             1.0,  1.0, 1.0, 0.0,    // bottom right 3
         ];
 
-        let transform_data = self.transform_data.concat();
-        
         unsafe {
             // create the shaderprogram
             let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
@@ -156,31 +160,32 @@ impl Object for Text {
 
             // create the texture buffer out of the image
             self.texture_buffer = TextureBuffer::new();
-            self.texture_buffer.set_data(&self.image_data.to_rgba_image());
+            self.texture_buffer.set_data(&self.image_data.to_rgba_image(), &TextureOptions::default());
           
             // create a new buffer for our transform data
-            self.transform_buffer = Buffer::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW);
-            self.transform_buffer.set_data(&transform_data);
+            self.transform_buffer.load();
             // and create the attributes in the vertex shader
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 32, 0 as *const _); // offset
-            gl::VertexAttribPointer(3, 2, gl::FLOAT, gl::FALSE, 32, 8 as *const _); // scale
-            gl::VertexAttribPointer(4, 4, gl::FLOAT, gl::FALSE, 32, 16 as *const _); // color
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 48, 0 as *const _); // offset
+            gl::VertexAttribPointer(3, 2, gl::FLOAT, gl::FALSE, 48, 8 as *const _); // scale
+            gl::VertexAttribPointer(4, 4, gl::FLOAT, gl::FALSE, 48, 16 as *const _); // color
+            gl::VertexAttribPointer(5, 4, gl::FLOAT, gl::FALSE, 48, 32 as *const _); // rotation
             gl::VertexAttribDivisor(2, 1);
             gl::VertexAttribDivisor(3, 1);
             gl::VertexAttribDivisor(4, 1);
+            gl::VertexAttribDivisor(5, 1);
             gl::EnableVertexAttribArray(2);
             gl::EnableVertexAttribArray(3);
             gl::EnableVertexAttribArray(4);
+            gl::EnableVertexAttribArray(5);
         }
 
         self.state = ObjectState::Ok;
         Ok(())
     }
 
-    // resets the transformation data
+    // uploads the transformation data that changed
     fn reload(&mut self) {
-        let transform_data = self.transform_data.concat();
-        self.transform_buffer.set_data(&transform_data);
+        self.transform_buffer.upload();
         self.state = ObjectState::Ok;
     }
 
@@ -211,7 +216,7 @@ impl Object for Text {
             gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
             gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
             gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
-            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_data.len() as i32);
+            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_buffer.draw_count() as i32);
         }
 
         Ok(())
@@ -221,4 +226,35 @@ impl Object for Text {
     fn set_state(&mut self, object_state: ObjectState) {
         self.state = object_state;
     }
-}
\ No newline at end of file
+
+    // only draw the instances in visible, None draws all
+    fn set_visible(&mut self, visible: Option<Vec<usize>>) {
+        if self.transform_buffer.set_visible(visible) {
+            self.state = ObjectState::Reload;
+        }
+    }
+
+    // replace the image of the rendered text,
+    // a loaded texture buffer gets updated right away
+    fn set_image(&mut self, image: &Image) -> Result<(), String> {
+        self.image_data = image.clone();
+        if self.texture_buffer.id != 0 {
+            self.texture_buffer.set_data(&self.image_data.to_rgba_image(), &TextureOptions::default());
+        }
+        Ok(())
+    }
+}
+
+// create the transform data of a single text
+fn transform_data(object_data: &ObjectData) -> TransformData {
+    let (x_offset, y_offset) = object_data.offset;
+    let (width, height) = object_data.dim;
+    let color = object_data.color; 
+    let opacity = object_data.opacity;
+    let rotation = object_data.rotation_data();
+
+    [
+        x_offset, y_offset, width, height, color.r, color.g, color.b, opacity,
+        rotation[0], rotation[1], rotation[2], rotation[3],
+    ]
+}
@@ -1,4 +1,4 @@
-use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, TextureBuffer};
+use crate::core::shader::object::{VertexArray, Buffer, ObjectState, Object, TextureBuffer, TextureOptions, InstanceBuffer};
 use crate::core::shader::program::Program;
 use crate::core::resource::image::Image;
 use crate::core::shader::data::ObjectData;
@@ -6,6 +6,7 @@ use crate::core::math::mvp;
 use crate::core::project::Draw;
 use crate::core::math::transform::Transform;
 use crate::core::shader::Shader;
+use std::rc::Rc;
 
 const VERTEX_SHADER_SOURCE: &str = r#"
     #version 330
@@ -15,6 +16,9 @@ const VERTEX_SHADER_SOURCE: &str = r#"
     layout (location = 3) in float opacity;
     layout (location = 4) in vec4 texcoord_1;
     layout (location = 5) in vec4 texcoord_2;
+    layout (location = 6) in vec4 rotation;
+    layout (location = 7) in vec4 tint_color;
+    layout (location = 8) in vec2 tint;
     
     uniform mat4 projection;
     uniform mat4 view;
@@ -22,12 +26,30 @@ const VERTEX_SHADER_SOURCE: &str = r#"
 
     out vec2 oTexCoord;
     out float oOpacity;
+    out vec4 oTintColor;
+    out vec2 oTint;
+
+    // mirror and rotate a point of the instance around its pivot,
+    // rotation holds the angle, the pivot as a fraction of size and the flip bits
+    vec2 rotate(vec2 local, vec2 size) {
+        int flip = int(rotation.w + 0.5);
+        vec2 mirrored = vec2(
+            (flip & 1) != 0 ? size.x - local.x : local.x,
+            (flip & 2) != 0 ? size.y - local.y : local.y
+        );
+        vec2 pivot = rotation.yz * size;
+        float s = sin(rotation.x);
+        float c = cos(rotation.x);
+        return mat2(c, s, -s, c) * (mirrored - pivot) + pivot;
+    }
 
     void main() {
         vec2 scale_position = position * scale;
-        vec2 offset_position = scale_position + offset;
+        vec2 offset_position = rotate(scale_position, scale) + offset;
         gl_Position = projection * view * model * vec4(offset_position, 0.0, 1.0);
         oOpacity = opacity;
+        oTintColor = tint_color;
+        oTint = tint;
 
         float tex_correction = 0.000001;
 
@@ -50,26 +72,46 @@ const FRAGMENT_SHADER_SOURCE: &str = r#"
 
     in vec2 oTexCoord;
     in float oOpacity;
+    in vec4 oTintColor;
+    in vec2 oTint;
 
     out vec4 color;
 
     void main() {
         vec4 t = texture(sampler, oTexCoord);
+
+        // tint holds the mode and the amount to lerp
+        int mode = int(oTint.x + 0.5);
+        if(mode == 1) {
+            t.rgb = t.rgb * oTintColor.rgb;
+        } else if(mode == 2) {
+            t.rgb = min(t.rgb + oTintColor.rgb, 1.0);
+        } else if(mode == 3) {
+            t.rgb = oTintColor.rgb;
+        } else if(mode == 4) {
+            t.rgb = mix(t.rgb, oTintColor.rgb, oTint.y);
+        }
+
         t.a = t.a * oOpacity;
         color = t;
+
+        // invisible pixels are not drawn so they don't count for stencil masks
+        if(color.a < 0.004) {
+            discard;
+        }
     }
 "#;
 
-type TransformData = [f32; 13];
+type TransformData = [f32; 23];
 
 pub struct Texture {
     program: Program,
     vertex_array: VertexArray,
     model_buffer: Buffer, // the buffer needs to stay alive
-    texture_buffer: TextureBuffer, // the buffer needs to stay alive
-    transform_buffer: Buffer, // the buffer needs to stay alive
-    transform_data: Vec<TransformData>,
-    image: Image,
+    texture_buffer: Rc<TextureBuffer>, // the buffer needs to stay alive
+    transform_buffer: InstanceBuffer<TransformData>, // the buffer needs to stay alive
+    image: Option<Image>,
+    options: TextureOptions,
     state: ObjectState,
 }
 
@@ -80,10 +122,32 @@ impl Texture {
             program: Program::default(),
             vertex_array: VertexArray::default(),
             model_buffer: Buffer::default(),
-            texture_buffer: TextureBuffer::default(),
-            transform_buffer: Buffer::default(),
-            transform_data: vec![],
-            image: image.clone(),
+            texture_buffer: Rc::new(TextureBuffer::default()),
+            transform_buffer: InstanceBuffer::new(),
+            image: Some(image.clone()),
+            options: TextureOptions::default(),
+            state: ObjectState::Ok,
+        }
+    }
+
+    // creates an empty Texture that is sampled with options
+    pub fn with_options(image: &Image, options: &TextureOptions) -> Self {
+        let mut texture = Self::new(image);
+        texture.options = *options;
+        texture
+    }
+
+    // creates an empty Texture that displays a texture buffer
+    // which is owned by something else e.g. a RenderTarget
+    pub fn from_buffer(texture_buffer: Rc<TextureBuffer>) -> Self {
+        Self {
+            program: Program::default(),
+            vertex_array: VertexArray::default(),
+            model_buffer: Buffer::default(),
+            texture_buffer,
+            transform_buffer: InstanceBuffer::new(),
+            image: None,
+            options: TextureOptions::default(),
             state: ObjectState::Ok,
         }
     }
@@ -92,47 +156,31 @@ impl Texture {
 impl Object for Texture {
     // add an new Texture to the transform data
     fn add(&mut self, object_data: &ObjectData) {
-        let texcoord = object_data.texcoord;
-        let (x_offset, y_offset) = object_data.offset;
-        let (width, height) = object_data.dim;
-        let opacity = object_data.opacity;
-
-        let transform_data: TransformData = [
-            x_offset, y_offset, width, height, opacity, 
-            texcoord[0], texcoord[1], texcoord[2], texcoord[3],
-            texcoord[4], texcoord[5], texcoord[6], texcoord[7], 
-        ];
-
-        self.transform_data.push(transform_data);
+        self.transform_buffer.push(transform_data(object_data));
     }
 
     /// set the component data
     /// for a specific element 'i' of the transform data vector
     fn set(&mut self, i: usize, object_data: &ObjectData) {
-        let texcoord = object_data.texcoord;
-        let (x_offset, y_offset) = object_data.offset;
-        let (width, height) = object_data.dim;
-        let opacity = object_data.opacity;
-
-        let transform_data: TransformData = [
-            x_offset, y_offset, width, height, opacity, 
-            texcoord[0], texcoord[1], texcoord[2], texcoord[3],
-            texcoord[4], texcoord[5], texcoord[6], texcoord[7], 
-        ];
-
-        self.transform_data[i] = transform_data;
+        self.transform_buffer.set(i, transform_data(object_data));
     }
 
     // removes a text from 
     // the transform data
     fn remove(&mut self, i: usize) {
-        self.transform_data.remove(i);
+        self.transform_buffer.remove(i);
+    }
+
+    // removes a texture by moving
+    // the last one into its place
+    fn swap_remove(&mut self, i: usize) {
+        self.transform_buffer.swap_remove(i);
     }
 
     // removes all rect from 
     // the transform data
     fn remove_all(&mut self) {
-        self.transform_data = vec![];
+        self.transform_buffer.clear();
     }
 
     // load the shaders 
@@ -145,8 +193,6 @@ impl Object for Texture {
             1.0,  1.0,      // bottom right tex 1.0, 0.0, 
         ];
 
-        let transform_data = self.transform_data.concat();
-        
         unsafe {
             // create the shaderprogram
             let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
@@ -165,38 +211,49 @@ impl Object for Texture {
             gl::EnableVertexAttribArray(0);
 
             // create the texture buffer out of the image
-            self.texture_buffer = TextureBuffer::new();
-            self.texture_buffer.set_data(&self.image.to_rgba_image());
+            // a shared texture buffer already holds its data
+            if let Some(image) = &self.image {
+                let texture_buffer = TextureBuffer::new();
+                texture_buffer.set_data(&image.to_rgba_image(), &self.options);
+                self.texture_buffer = Rc::new(texture_buffer);
+            }
           
             // create a new buffer for our transform data
-            self.transform_buffer = Buffer::new(gl::ARRAY_BUFFER, gl::DYNAMIC_DRAW);
-            self.transform_buffer.set_data(&transform_data);
+            self.transform_buffer.load();
             // and create the attributes in the vertex shader
-            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 52, 0 as *const _); // offset
-            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 52, 8 as *const _); // scale
-            gl::VertexAttribPointer(3, 1, gl::FLOAT, gl::FALSE, 52, 16 as *const _); // opacity
-            gl::VertexAttribPointer(4, 4, gl::FLOAT, gl::FALSE, 52, 20 as *const _); // texcoord_1
-            gl::VertexAttribPointer(5, 4, gl::FLOAT, gl::FALSE, 52, 36 as *const _); // texcoord_2
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, 92, 0 as *const _); // offset
+            gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, 92, 8 as *const _); // scale
+            gl::VertexAttribPointer(3, 1, gl::FLOAT, gl::FALSE, 92, 16 as *const _); // opacity
+            gl::VertexAttribPointer(4, 4, gl::FLOAT, gl::FALSE, 92, 20 as *const _); // texcoord_1
+            gl::VertexAttribPointer(5, 4, gl::FLOAT, gl::FALSE, 92, 36 as *const _); // texcoord_2
+            gl::VertexAttribPointer(6, 4, gl::FLOAT, gl::FALSE, 92, 52 as *const _); // rotation
+            gl::VertexAttribPointer(7, 4, gl::FLOAT, gl::FALSE, 92, 68 as *const _); // tint_color
+            gl::VertexAttribPointer(8, 2, gl::FLOAT, gl::FALSE, 92, 84 as *const _); // tint
             gl::VertexAttribDivisor(1, 1);
             gl::VertexAttribDivisor(2, 1);
             gl::VertexAttribDivisor(3, 1);
             gl::VertexAttribDivisor(4, 1);
             gl::VertexAttribDivisor(5, 1);
+            gl::VertexAttribDivisor(6, 1);
+            gl::VertexAttribDivisor(7, 1);
+            gl::VertexAttribDivisor(8, 1);
             gl::EnableVertexAttribArray(1);
             gl::EnableVertexAttribArray(2);
             gl::EnableVertexAttribArray(3);
             gl::EnableVertexAttribArray(4);
             gl::EnableVertexAttribArray(5);
+            gl::EnableVertexAttribArray(6);
+            gl::EnableVertexAttribArray(7);
+            gl::EnableVertexAttribArray(8);
         }
 
         self.state = ObjectState::Ok;
         Ok(())
     }
 
-    // resets the transformation data
+    // uploads the transformation data that changed
     fn reload(&mut self) {
-        let transform_data = self.transform_data.concat();
-        self.transform_buffer.set_data(&transform_data);
+        self.transform_buffer.upload();
         self.state = ObjectState::Ok;
     }
 
@@ -227,7 +284,7 @@ impl Object for Texture {
             gl::UniformMatrix4fv(projection_location, 1, gl::FALSE, projection.as_ptr());
             gl::UniformMatrix4fv(view_location, 1, gl::FALSE, view.as_ptr());
             gl::UniformMatrix4fv(model_location, 1, gl::FALSE, model.as_ptr());
-            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_data.len() as i32);
+            gl::DrawArraysInstanced(gl::TRIANGLE_FAN, 0, 4, self.transform_buffer.draw_count() as i32);
         }
 
         Ok(())
@@ -237,4 +294,72 @@ impl Object for Texture {
     fn set_state(&mut self, object_state: ObjectState) {
         self.state = object_state;
     }
+
+    // only draw the instances in visible, None draws all
+    fn set_visible(&mut self, visible: Option<Vec<usize>>) {
+        if self.transform_buffer.set_visible(visible) {
+            self.state = ObjectState::Reload;
+        }
+    }
+
+    // replace the image,
+    // a loaded texture gets a new texture buffer right away
+    fn set_image(&mut self, image: &Image) -> Result<(), String> {
+        self.image = Some(image.clone());
+        if self.texture_buffer.id != 0 {
+            // a shared texture buffer stays untouched
+            let texture_buffer = TextureBuffer::new();
+            texture_buffer.set_data(&image.to_rgba_image(), &self.options);
+            self.texture_buffer = Rc::new(texture_buffer);
+        }
+        Ok(())
+    }
+
+    // replace a part of the image starting at x and y from the top left
+    fn set_sub_image(&mut self, x: u32, y: u32, image: &Image) -> Result<(), String> {
+        let own_image = match &mut self.image {
+            Some(own_image) => own_image,
+            None => return Err("This texture displays a texture buffer without an image.".to_string()),
+        };
+        own_image.replace(x, y, image)?;
+
+        if self.texture_buffer.id != 0 {
+            // opengl starts bottom left
+            let gl_y = own_image.height - y as f32 - image.height;
+            self.texture_buffer.set_sub_data(&image.to_rgba_image(), x as i32, gl_y as i32);
+            if self.options.mipmaps {
+                self.texture_buffer.set_options(&self.options);
+            }
+        }
+        Ok(())
+    }
+
+    // change how the texture is sampled,
+    // a loaded texture buffer gets updated right away
+    fn set_texture_options(&mut self, options: &TextureOptions) -> Result<(), String> {
+        self.options = *options;
+        if self.texture_buffer.id != 0 {
+            self.texture_buffer.set_options(options);
+        }
+        Ok(())
+    }
+}
+
+// create the transform data of a single texture
+fn transform_data(object_data: &ObjectData) -> TransformData {
+    let texcoord = object_data.texcoord;
+    let (x_offset, y_offset) = object_data.offset;
+    let (width, height) = object_data.dim;
+    let opacity = object_data.opacity;
+    let rotation = object_data.rotation_data();
+    let color = object_data.color;
+    let tint = object_data.tint.data();
+
+    [
+        x_offset, y_offset, width, height, opacity, 
+        texcoord[0], texcoord[1], texcoord[2], texcoord[3],
+        texcoord[4], texcoord[5], texcoord[6], texcoord[7], 
+        rotation[0], rotation[1], rotation[2], rotation[3],
+        color.r, color.g, color.b, color.a, tint[0], tint[1],
+    ]
 }
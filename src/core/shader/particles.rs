@@ -0,0 +1,488 @@
+use crate::core::color::Color;
+use crate::core::math::collision::rotate_point;
+use crate::core::math::transform::Transform;
+use crate::core::project::Draw;
+use crate::core::resource::tilemap::{TileData, TileDataRotation};
+use crate::core::seed::Seed;
+use crate::core::shader::data::ObjectData;
+use crate::core::shader::instanced::InstancedShader;
+
+// the area new particles appear in around the emitter
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum EmitterShape {
+    #[default]
+    Point,
+    // a disc with a radius
+    Circle(f32),
+    // a rect of width and height around the emitter
+    Rect(f32, f32),
+    // a line from the emitter to x and y
+    Line(f32, f32),
+}
+
+// where the particles live after they are emitted
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ParticleSpace {
+    // particles stay where they were emitted when the emitter moves
+    #[default]
+    World,
+    // particles move with the emitter
+    Local,
+}
+
+// values that can be mixed in a Curve
+pub trait Mix: Copy {
+    fn mix(a: Self, b: Self, t: f32) -> Self;
+}
+
+impl Mix for f32 {
+    fn mix(a: f32, b: f32, t: f32) -> f32 {
+        a + (b - a) * t
+    }
+}
+
+impl Mix for Color {
+    fn mix(a: Color, b: Color, t: f32) -> Color {
+        Color {
+            r: f32::mix(a.r, b.r, t),
+            g: f32::mix(a.g, b.g, t),
+            b: f32::mix(a.b, b.b, t),
+            a: f32::mix(a.a, b.a, t),
+        }
+    }
+}
+
+/// a value that changes over the life of a particle,
+/// the keys are at times from 0.0 (born) to 1.0 (dead)
+/// ```rust
+/// let fade = Curve::new(vec![(0.0, 1.0), (0.8, 1.0), (1.0, 0.0)]);
+/// let fire = Curve::new(vec![(0.0, Color::rgb(255, 220, 80)), (1.0, Color::rgb(200, 30, 0))]);
+/// ```
+#[derive(Clone)]
+pub struct Curve<T> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Mix> Curve<T> {
+    // create a curve out of keys, they get sorted by time
+    pub fn new(mut keys: Vec<(f32, T)>) -> Self {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keys }
+    }
+
+    // a curve that never changes
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    // the value at time t
+    pub fn sample(&self, t: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        if t <= first.0 {
+            return Some(first.1);
+        }
+
+        for pair in self.keys.windows(2) {
+            let ((from, a), (to, b)) = (pair[0], pair[1]);
+            if t <= to {
+                return Some(T::mix(a, b, (t - from) / (to - from).max(0.0001)));
+            }
+        }
+
+        self.keys.last().map(|key| key.1)
+    }
+}
+
+/// everything that describes how an Emitter
+/// emits and moves its particles
+/// ```rust
+/// let sparks = EmitterSettings {
+///     shape: EmitterShape::Circle(10.0),
+///     rate: 200.0,
+///     lifetime: (0.3, 0.6),
+///     speed: (100.0, 250.0),
+///     spread: std::f32::consts::TAU,
+///     gravity: (0.0, 400.0),
+///     opacity: Curve::new(vec![(0.0, 1.0), (1.0, 0.0)]),
+///     ..EmitterSettings::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct EmitterSettings {
+    pub shape: EmitterShape,
+    // particles every second
+    pub rate: f32,
+    // the min and max seconds a particle lives
+    pub lifetime: (f32, f32),
+    // the min and max speed in pixels per second
+    pub speed: (f32, f32),
+    // the direction particles fly in radians, 0.0 is right
+    pub angle: f32,
+    // the whole angle around the direction in radians
+    pub spread: f32,
+    // added to the velocity every second
+    pub gravity: (f32, f32),
+    // the part of the velocity that is lost every second
+    pub drag: f32,
+    // the min and max size in pixels
+    pub size: (f32, f32),
+    // the size is multiplied by this curve
+    pub size_curve: Curve<f32>,
+    pub color: Curve<Color>,
+    pub opacity: Curve<f32>,
+    // the min and max rotation speed in radians per second
+    pub spin: (f32, f32),
+    // the frames of a texture played over the life of a particle,
+    // empty uses the whole texture
+    pub frames: Vec<TileData>,
+    pub space: ParticleSpace,
+    // no more particles are emitted once this many are alive
+    pub max_particles: usize,
+}
+
+impl Default for EmitterSettings {
+    // returns white particles that fly up for one second
+    fn default() -> Self {
+        Self {
+            shape: EmitterShape::Point,
+            rate: 10.0,
+            lifetime: (1.0, 1.0),
+            speed: (50.0, 50.0),
+            angle: -std::f32::consts::FRAC_PI_2,
+            spread: 0.0,
+            gravity: (0.0, 0.0),
+            drag: 0.0,
+            size: (4.0, 4.0),
+            size_curve: Curve::constant(1.0),
+            color: Curve::constant(Color::grey(255)),
+            opacity: Curve::constant(1.0),
+            spin: (0.0, 0.0),
+            frames: vec![],
+            space: ParticleSpace::World,
+            max_particles: 1000,
+        }
+    }
+}
+
+// a single particle
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    position: (f32, f32),
+    velocity: (f32, f32),
+    age: f32,
+    lifetime: f32,
+    size: f32,
+    rotation: f32,
+    spin: f32,
+}
+
+/*
+the particles of an emitter without anything to draw them,
+the seed makes every run with the same updates the same
+*/
+struct Simulation {
+    particles: Vec<Particle>,
+    seed: Seed,
+    // the part of a particle that was not emitted yet
+    pending: f32,
+}
+
+impl Simulation {
+    fn new(seed: Seed) -> Self {
+        Self {
+            particles: vec![],
+            seed,
+            pending: 0.0,
+        }
+    }
+
+    // emit count particles at the emitter transform
+    fn emit(&mut self, settings: &EmitterSettings, emitter: &Transform, count: usize) {
+        let count = count.min(settings.max_particles.saturating_sub(self.particles.len()));
+
+        for _ in 0..count {
+            let (x, y) = self.spawn_offset(settings.shape);
+            let angle = settings.angle + (self.next() - 0.5) * settings.spread;
+            let speed = self.range(settings.speed);
+
+            let velocity = (angle.cos() * speed, angle.sin() * speed);
+
+            // world particles start where the emitter is right now
+            // and fly in the direction it is turned to
+            let (position, velocity) = match settings.space {
+                ParticleSpace::World => {
+                    let matrix = emitter.matrix();
+                    let point = matrix * nalgebra_glm::vec4(x, y, 0.0, 1.0);
+                    let direction = matrix * nalgebra_glm::vec4(velocity.0, velocity.1, 0.0, 0.0);
+                    ((point.x, point.y), (direction.x, direction.y))
+                }
+                ParticleSpace::Local => ((x, y), velocity),
+            };
+
+            let particle = Particle {
+                position,
+                velocity,
+                age: 0.0,
+                lifetime: self.range(settings.lifetime).max(0.0001),
+                size: self.range(settings.size),
+                rotation: 0.0,
+                spin: self.range(settings.spin),
+            };
+            self.particles.push(particle);
+        }
+    }
+
+    // emit new particles and move all particles delta seconds
+    fn update(&mut self, settings: &EmitterSettings, emitter: &Transform, emitting: bool, delta: f32) {
+        let drag = (-settings.drag * delta).exp();
+        for particle in &mut self.particles {
+            particle.age += delta;
+            particle.velocity.0 = (particle.velocity.0 + settings.gravity.0 * delta) * drag;
+            particle.velocity.1 = (particle.velocity.1 + settings.gravity.1 * delta) * drag;
+            particle.position.0 += particle.velocity.0 * delta;
+            particle.position.1 += particle.velocity.1 * delta;
+            particle.rotation += particle.spin * delta;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+
+        if emitting {
+            self.pending += settings.rate * delta;
+            let count = self.pending.floor();
+            self.pending -= count;
+            self.emit(settings, emitter, count as usize);
+        }
+    }
+
+    // the component data of all particles
+    fn instances(&self, settings: &EmitterSettings) -> Vec<ObjectData> {
+        let white = Color::grey(255);
+        let frames: Vec<_> = settings.frames.iter().map(|frame| frame.texcoord(&TileDataRotation::Bottom)).collect();
+
+        self.particles
+            .iter()
+            .map(|particle| {
+                let t = (particle.age / particle.lifetime).min(1.0);
+                let size = particle.size * settings.size_curve.sample(t).unwrap_or(1.0);
+                let (x, y) = particle.position;
+
+                let mut object_data = ObjectData {
+                    offset: (x - size / 2.0, y - size / 2.0),
+                    dim: (size, size),
+                    color: settings.color.sample(t).unwrap_or(white),
+                    opacity: settings.opacity.sample(t).unwrap_or(1.0),
+                    rotation: particle.rotation,
+                    ..ObjectData::default()
+                };
+                if !frames.is_empty() {
+                    let frame = ((t * frames.len() as f32) as usize).min(frames.len() - 1);
+                    object_data.texcoord = frames[frame];
+                }
+                object_data
+            })
+            .collect()
+    }
+
+    // a random point of the shape
+    fn spawn_offset(&mut self, shape: EmitterShape) -> (f32, f32) {
+        match shape {
+            EmitterShape::Point => (0.0, 0.0),
+            EmitterShape::Circle(radius) => {
+                // the square root spreads the points evenly over the disc
+                let distance = radius * self.next().sqrt();
+                rotate_point(distance, 0.0, 0.0, 0.0, self.next() * std::f32::consts::TAU)
+            }
+            EmitterShape::Rect(width, height) => ((self.next() - 0.5) * width, (self.next() - 0.5) * height),
+            EmitterShape::Line(x, y) => {
+                let t = self.next();
+                (x * t, y * t)
+            }
+        }
+    }
+
+    // a random number from 0.0 to 1.0
+    fn next(&mut self) -> f32 {
+        self.seed.next_f32()
+    }
+
+    // a random number from min to max
+    fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next()
+    }
+}
+
+/// emits particles and draws all of them with one InstancedShader,
+/// any InstancedShader can be used, e.g. circles or a texture
+/// ```rust
+/// let shader = InstancedShader::circle().unwrap();
+/// let mut sparks = Emitter::new(shader, settings, Seed::from_str("sparks")).unwrap();
+/// sparks.shader_mut().set_blend(BlendMode::Additive);
+/// sparks.transform.set(400.0, 300.0, 0.0);
+///
+/// // every frame
+/// sparks.update(draw.performance.delta());
+/// sparks.draw(&draw, &camera)?;
+/// ```
+pub struct Emitter {
+    // where the particles are emitted
+    pub transform: Transform,
+    pub settings: EmitterSettings,
+    shader: InstancedShader,
+    simulation: Simulation,
+    emitting: bool,
+}
+
+impl Emitter {
+    // create an emitter that draws its particles with the shader
+    pub fn new(mut shader: InstancedShader, settings: EmitterSettings, seed: Seed) -> Result<Self, String> {
+        shader.load()?;
+
+        Ok(Self {
+            transform: Transform::new(),
+            settings,
+            shader,
+            simulation: Simulation::new(seed),
+            emitting: true,
+        })
+    }
+
+    // start or stop emitting at the rate, bursts still work
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+    }
+
+    // true if particles are emitted at the rate
+    pub fn emitting(&self) -> bool {
+        self.emitting
+    }
+
+    // emit count particles at once
+    pub fn burst(&mut self, count: usize) {
+        self.simulation.emit(&self.settings, &self.transform, count);
+    }
+
+    // emit new particles and move all particles,
+    // delta is the time since the last frame in seconds
+    pub fn update(&mut self, delta: f32) {
+        self.simulation.update(&self.settings, &self.transform, self.emitting, delta);
+    }
+
+    // the number of particles that are alive
+    pub fn len(&self) -> usize {
+        self.simulation.particles.len()
+    }
+
+    // true if no particle is alive
+    pub fn is_empty(&self) -> bool {
+        self.simulation.particles.is_empty()
+    }
+
+    // remove all particles
+    pub fn clear(&mut self) {
+        self.simulation.particles.clear();
+    }
+
+    // the shader that draws the particles, e.g. to change the blend mode
+    pub fn shader_mut(&mut self) -> &mut InstancedShader {
+        &mut self.shader
+    }
+
+    // draw all particles in one draw call
+    pub fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
+        self.shader.transform = match self.settings.space {
+            ParticleSpace::World => Transform::new(),
+            ParticleSpace::Local => self.transform,
+        };
+
+        self.shader.remove_all();
+        self.shader.extend(self.simulation.instances(&self.settings));
+        self.shader.draw(draw, camera)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve() {
+        let curve = Curve::new(vec![(1.0, 0.0), (0.0, 1.0), (0.5, 1.0)]);
+        assert_eq!(Some(1.0), curve.sample(-1.0));
+        assert_eq!(Some(1.0), curve.sample(0.25));
+        assert_eq!(Some(0.5), curve.sample(0.75));
+        assert_eq!(Some(0.0), curve.sample(2.0));
+        assert_eq!(None, Curve::<f32>::new(vec![]).sample(0.5));
+    }
+
+    #[test]
+    fn test_simulation() {
+        let settings = EmitterSettings {
+            rate: 100.0,
+            lifetime: (0.5, 0.5),
+            speed: (10.0, 10.0),
+            angle: 0.0,
+            gravity: (0.0, 20.0),
+            max_particles: 30,
+            ..EmitterSettings::default()
+        };
+        let mut emitter = Transform::new();
+        emitter.set(100.0, 50.0, 0.0);
+
+        let mut simulation = Simulation::new(Seed::from_str("test"));
+        simulation.update(&settings, &emitter, true, 0.1);
+        assert_eq!(10, simulation.particles.len());
+        assert_eq!((100.0, 50.0), simulation.particles[0].position);
+
+        simulation.update(&settings, &emitter, false, 0.1);
+        let particle = simulation.particles[0];
+        assert!((particle.velocity.1 - 2.0).abs() < 0.001);
+        assert!((particle.position.0 - 101.0).abs() < 0.001);
+
+        // the max stops emitting, dead particles are removed
+        simulation.emit(&settings, &emitter, 100);
+        assert_eq!(30, simulation.particles.len());
+        simulation.update(&settings, &emitter, false, 0.45);
+        assert_eq!(20, simulation.particles.len());
+    }
+
+    #[test]
+    fn test_world_direction() {
+        let settings = EmitterSettings {
+            speed: (10.0, 10.0),
+            angle: 0.0,
+            ..EmitterSettings::default()
+        };
+        let mut emitter = Transform::new();
+        emitter.set_rotation(std::f32::consts::FRAC_PI_2, 0.0, 0.0, 1.0);
+
+        // world particles fly where the emitter is turned to
+        let mut simulation = Simulation::new(Seed::from_str("test"));
+        simulation.emit(&settings, &emitter, 1);
+        let (x, y) = simulation.particles[0].velocity;
+        assert!(x.abs() < 0.001 && (y - 10.0).abs() < 0.001);
+
+        // local particles get turned when they are drawn
+        let local = EmitterSettings { space: ParticleSpace::Local, ..settings };
+        simulation.emit(&local, &emitter, 1);
+        assert_eq!((10.0, 0.0), simulation.particles[1].velocity);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let settings = EmitterSettings {
+            shape: EmitterShape::Circle(50.0),
+            ..EmitterSettings::default()
+        };
+        let emitter = Transform::new();
+
+        let mut a = Simulation::new(Seed::from_str("same"));
+        let mut b = Simulation::new(Seed::from_str("same"));
+        a.emit(&settings, &emitter, 20);
+        b.emit(&settings, &emitter, 20);
+        for (a, b) in a.particles.iter().zip(&b.particles) {
+            assert_eq!(a.position, b.position);
+            assert!(a.position.0.hypot(a.position.1) <= 50.0);
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use crate::core::shader::object::{Buffer, VertexArray};
+use crate::core::shader::program::Program;
+use crate::core::shader::target::{FramebufferState, RenderTarget};
+use crate::core::shader::Shader;
+use crate::core::window::Window;
+use crate::Draw;
+
+// every pass draws a single rectangle over the whole screen
+const VERTEX_SHADER_SOURCE: &str = r#"
+    #version 330
+    layout (location = 0) in vec2 position;
+
+    out vec2 oTexCoord;
+
+    void main() {
+        gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+        oTexCoord = position;
+    }
+"#;
+
+const COPY_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        color = texture(sampler, oTexCoord);
+    }
+"#;
+
+const BLUR_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+    uniform vec2 resolution;
+    uniform vec4 params;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        vec2 texel = params.x / 4.0 / resolution;
+        vec4 sum = vec4(0.0);
+        float weights = 0.0;
+
+        for(int x = -4; x <= 4; x++) {
+            for(int y = -4; y <= 4; y++) {
+                float weight = exp(-float(x * x + y * y) / 8.0);
+                sum += texture(sampler, oTexCoord + vec2(x, y) * texel) * weight;
+                weights += weight;
+            }
+        }
+
+        color = sum / weights;
+    }
+"#;
+
+const BLOOM_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+    uniform vec2 resolution;
+    uniform vec4 params;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        vec2 texel = 2.0 / resolution;
+        vec3 glow = vec3(0.0);
+        float weights = 0.0;
+
+        for(int x = -4; x <= 4; x++) {
+            for(int y = -4; y <= 4; y++) {
+                float weight = exp(-float(x * x + y * y) / 8.0);
+                vec3 t = texture(sampler, oTexCoord + vec2(x, y) * texel).rgb;
+                float brightness = dot(t, vec3(0.2126, 0.7152, 0.0722));
+                glow += t * step(params.x, brightness) * weight;
+                weights += weight;
+            }
+        }
+
+        vec4 t = texture(sampler, oTexCoord);
+        color = vec4(t.rgb + glow / weights * params.y, t.a);
+    }
+"#;
+
+const VIGNETTE_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+    uniform vec4 params;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        vec4 t = texture(sampler, oTexCoord);
+        float dist = distance(oTexCoord, vec2(0.5));
+        float vignette = smoothstep(params.x, params.x - params.y, dist);
+        color = vec4(t.rgb * vignette, t.a);
+    }
+"#;
+
+const CRT_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+    uniform vec2 resolution;
+    uniform vec4 params;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        // bend the screen like an old monitor
+        vec2 uv = oTexCoord * 2.0 - 1.0;
+        uv *= 1.0 + params.x * dot(uv.yx, uv.yx);
+        uv = uv * 0.5 + 0.5;
+
+        if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+            color = vec4(0.0, 0.0, 0.0, 1.0);
+            return;
+        }
+
+        vec4 t = texture(sampler, uv);
+        float scanline = 1.0 - params.y * (0.5 + 0.5 * sin(uv.y * resolution.y * 3.14159));
+        color = vec4(t.rgb * scanline, t.a);
+    }
+"#;
+
+const GRAYSCALE_SHADER_SOURCE: &str = r#"
+    #version 330
+    uniform sampler2D sampler;
+
+    in vec2 oTexCoord;
+
+    out vec4 color;
+
+    void main() {
+        vec4 t = texture(sampler, oTexCoord);
+        float grey = dot(t.rgb, vec3(0.2126, 0.7152, 0.0722));
+        color = vec4(vec3(grey), t.a);
+    }
+"#;
+
+/*
+a pass is one fullscreen effect of the PostProcess chain
+
+a Custom pass takes the source code of a fragment shader,
+it can use the following inputs:
+    uniform sampler2D sampler;  the image of the previous pass
+    uniform vec2 resolution;    the size of the image in pixels
+    uniform float time;         seconds since the PostProcess was created
+    in vec2 oTexCoord;          the texture coordinate of the pixel
+*/
+#[derive(Clone)]
+pub enum Pass {
+    Blur { radius: f32 },
+    Bloom { threshold: f32, intensity: f32 },
+    Vignette { radius: f32, softness: f32 },
+    Crt { curvature: f32, scanlines: f32 },
+    Grayscale,
+    Custom(String),
+}
+
+impl Pass {
+    // the fragment shader of the pass
+    fn source(&self) -> &str {
+        match self {
+            Pass::Blur { .. } => BLUR_SHADER_SOURCE,
+            Pass::Bloom { .. } => BLOOM_SHADER_SOURCE,
+            Pass::Vignette { .. } => VIGNETTE_SHADER_SOURCE,
+            Pass::Crt { .. } => CRT_SHADER_SOURCE,
+            Pass::Grayscale => GRAYSCALE_SHADER_SOURCE,
+            Pass::Custom(source) => source,
+        }
+    }
+
+    // the settings of the pass
+    // send to the shader as the params uniform
+    fn params(&self) -> [f32; 4] {
+        match self {
+            Pass::Blur { radius } => [*radius, 0.0, 0.0, 0.0],
+            Pass::Bloom { threshold, intensity } => [*threshold, *intensity, 0.0, 0.0],
+            Pass::Vignette { radius, softness } => [*radius, *softness, 0.0, 0.0],
+            Pass::Crt { curvature, scanlines } => [*curvature, *scanlines, 0.0, 0.0],
+            Pass::Grayscale | Pass::Custom(_) => [0.0; 4],
+        }
+    }
+}
+
+// a pass with its compiled shader program
+struct Effect {
+    pass: Pass,
+    program: Program,
+}
+
+/// A chain of fullscreen effects applied to everything drawn
+/// between begin and end
+///
+/// create the chain in load
+/// ```rust
+/// let mut post = PostProcess::new(&Window::new(1270.0, 700.0)).unwrap();
+/// post.add(Pass::Bloom { threshold: 0.8, intensity: 1.5 }).unwrap();
+/// post.add(Pass::Vignette { radius: 0.75, softness: 0.45 }).unwrap();
+/// ```
+///
+/// wrap the draw calls in update
+/// ```rust
+/// post.begin(&draw).unwrap();
+/// rect.draw(&draw, &camera).unwrap();
+/// post.end(&draw).unwrap();
+/// ```
+pub struct PostProcess {
+    scene: RenderTarget,
+    swap: RenderTarget,
+    copy: Program,
+    effects: Vec<Effect>,
+    vertex_array: VertexArray,
+    model_buffer: Buffer, // the buffer needs to stay alive
+    time: f32,
+    // where the result goes, what was drawn to before begin
+    output: FramebufferState,
+}
+
+impl PostProcess {
+    // create an empty PostProcess chain with the size of the window
+    pub fn new(window: &Window) -> Result<Self, String> {
+        let model_data: [f32; 4 * 2] = [
+            1.0, 0.0, // bottom right 0
+            0.0, 0.0, // bottom left 1
+            0.0, 1.0, // top left 2
+            1.0, 1.0, // top right 3
+        ];
+
+        let post_process = Self {
+            scene: RenderTarget::window(window)?,
+            swap: RenderTarget::window(window)?,
+            copy: Self::program(COPY_SHADER_SOURCE)?,
+            effects: vec![],
+            vertex_array: VertexArray::new(),
+            model_buffer: Buffer::new(gl::ARRAY_BUFFER, gl::STATIC_DRAW),
+            time: 0.0,
+            output: FramebufferState::default(),
+        };
+
+        // create the buffer for the fullscreen rectangle
+        post_process.vertex_array.bind();
+        post_process.model_buffer.set_data(&model_data.to_vec());
+
+        unsafe {
+            // and create the attributes in the vertex shader
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 8, std::ptr::null()); // position
+            gl::EnableVertexAttribArray(0);
+        }
+
+        Ok(post_process)
+    }
+
+    // add a pass to the end of the chain
+    pub fn add(&mut self, pass: Pass) -> Result<(), String> {
+        let program = Self::program(pass.source())?;
+        self.effects.push(Effect { pass, program });
+        Ok(())
+    }
+
+    // replace pass i of the chain
+    pub fn set(&mut self, i: usize, pass: Pass) -> Result<(), String> {
+        self.index_oob(i)?;
+
+        // only recompile when the shader source changes
+        if pass.source() != self.effects[i].pass.source() {
+            self.effects[i].program = Self::program(pass.source())?;
+        }
+
+        self.effects[i].pass = pass;
+        Ok(())
+    }
+
+    // get a copy of pass i
+    pub fn get(&self, i: usize) -> Result<Pass, String> {
+        self.index_oob(i)?;
+        Ok(self.effects[i].pass.clone())
+    }
+
+    // remove pass i from the chain
+    pub fn remove(&mut self, i: usize) -> Result<(), String> {
+        self.index_oob(i)?;
+        self.effects.remove(i);
+        Ok(())
+    }
+
+    // remove all passes from the chain
+    pub fn remove_all(&mut self) {
+        self.effects = vec![];
+    }
+
+    // the number of passes in the chain
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    // true if the chain has no passes
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    // redirect all following draw calls into the scene
+    pub fn begin(&mut self, draw: &Draw) -> Result<(), String> {
+        self.output = FramebufferState::current();
+        self.scene.bind(draw)?;
+
+        unsafe {
+            // clear with the background color of the window
+            // and start without masks
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+        }
+
+        Ok(())
+    }
+
+    // apply all passes to the scene and draw the result
+    // to what was drawn to before begin, usually the window
+    pub fn end(&mut self, draw: &Draw) -> Result<(), String> {
+        self.time += draw.performance.delta();
+
+        // make sure the swap target has the size of the window
+        self.swap.bind(draw)?;
+
+        unsafe {
+            // every pass replaces the pixels of its destination
+            let blend = gl::IsEnabled(gl::BLEND);
+            gl::Disable(gl::BLEND);
+
+            let result = self.apply(draw);
+
+            if blend == gl::TRUE {
+                gl::Enable(gl::BLEND);
+            }
+
+            result
+        }
+    }
+
+    // draw the scene through every pass
+    // the last pass ends up in the output
+    fn apply(&mut self, draw: &Draw) -> Result<(), String> {
+        let mut source_is_scene = true;
+        let count = self.effects.len();
+
+        // without passes the scene is copied to the output
+        if count == 0 {
+            self.output.restore();
+            let resolution = self.scene.dim();
+            Self::pass(&self.vertex_array, &self.copy, &self.scene, resolution, [0.0; 4], self.time)?;
+            return Ok(());
+        }
+
+        for (i, effect) in self.effects.iter().enumerate() {
+            let (source, destination) = if source_is_scene {
+                (&mut self.scene, &mut self.swap)
+            } else {
+                (&mut self.swap, &mut self.scene)
+            };
+
+            if i + 1 == count {
+                self.output.restore();
+            } else {
+                destination.bind(draw)?;
+            }
+
+            let resolution = source.dim();
+            let params = effect.pass.params();
+            Self::pass(&self.vertex_array, &effect.program, source, resolution, params, self.time)?;
+            source_is_scene = !source_is_scene;
+        }
+
+        Ok(())
+    }
+
+    // draw the source target with a program
+    // into the currently bound framebuffer
+    fn pass(
+        vertex_array: &VertexArray,
+        program: &Program,
+        source: &RenderTarget,
+        resolution: (f32, f32),
+        params: [f32; 4],
+        time: f32,
+    ) -> Result<(), String> {
+        unsafe {
+            program.bind();
+            vertex_array.bind();
+            source.texture_buffer().bind();
+
+            let resolution_location = program.get_uniform_location("resolution")?;
+            let params_location = program.get_uniform_location("params")?;
+            let time_location = program.get_uniform_location("time")?;
+            gl::Uniform2f(resolution_location, resolution.0, resolution.1);
+            gl::Uniform4fv(params_location, 1, params.as_ptr());
+            gl::Uniform1f(time_location, time);
+            gl::DrawArrays(gl::TRIANGLE_FAN, 0, 4);
+        }
+
+        Ok(())
+    }
+
+    // compile a fullscreen shader program
+    fn program(fragment_source: &str) -> Result<Program, String> {
+        let vertex_shader = Shader::new(VERTEX_SHADER_SOURCE, gl::VERTEX_SHADER)?;
+        let fragment_shader = Shader::new(fragment_source, gl::FRAGMENT_SHADER)?;
+        Program::new(&vertex_shader, &fragment_shader)
+    }
+
+    // checks if a pass is in the chain
+    fn index_oob(&self, i: usize) -> Result<(), String> {
+        if i >= self.effects.len() {
+            Err(format!("Pass with index '{}' not found.", i))
+        } else {
+            Ok(())
+        }
+    }
+}
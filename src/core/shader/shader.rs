@@ -1,9 +1,17 @@
-use crate::collision::point_in_rect;
+use crate::collision::{point_in_rect, rotate_point};
+use crate::core::math::culling;
 use crate::core::color::Color;
 use crate::core::resource::font::Font;
-use crate::core::shader::object::TextureCoordinate;
+use crate::core::shader::object::{TextureCoordinate, TextureOptions};
 use crate::core::shader::object::{circle::Circle, rect::Rect, text::Text, texture::Texture};
+use crate::core::shader::object::line::{Line, Polyline};
+use crate::core::shader::object::nineslice::{NineSlice, NineSliceData};
+use crate::core::resource::tilemap::{TileData, TileMap};
+use crate::core::shader::object::polygon::{Polygon, PolygonData};
 use crate::core::shader::object::{Object, ObjectState};
+use crate::core::shader::target::RenderTarget;
+use crate::core::shader::blend::BlendMode;
+use crate::core::shader::data::{Gradient, Shadow, TintMode};
 use crate::{Draw, Image, ObjectData, Transform};
 
 /// The api to draw to the screen
@@ -24,6 +32,8 @@ pub struct Shader {
     pub transform: Transform,
     object_data: ObjectData,
     object: Box<dyn Object>,
+    blend: BlendMode,
+    culling: bool,
 }
 
 impl Shader {
@@ -37,13 +47,7 @@ impl Shader {
         rect.add(&object_data);
         rect.load()?;
 
-        let component = Self {
-            object: Box::new(rect),
-            object_data,
-            transform: Transform::default(),
-        };
-
-        Ok(component)
+        Ok(Self::from_object(Box::new(rect), object_data))
     }
 
     // create a new circle Shader
@@ -56,13 +60,41 @@ impl Shader {
         circle.add(&object_data);
         circle.load()?;
 
-        let component = Self {
-            object: Box::new(circle),
-            object_data,
-            transform: Transform::default(),
-        };
+        Ok(Self::from_object(Box::new(circle), object_data))
+    }
+
+    // create a new line Shader from x1, y1 to x2, y2
+    pub fn line(x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: &Color) -> Result<Self, String> {
+        let polyline = Polyline::line(x1, y1, x2, y2, thickness, color);
+        Self::polyline(&polyline)
+    }
 
-        Ok(component)
+    // create a new polyline Shader
+    // the offset and dim are the rect around the points
+    pub fn polyline(polyline: &Polyline) -> Result<Self, String> {
+        // the object data of a line holds
+        // the color of all segments
+        let mut object_data = ObjectData::default();
+        if let Some(color) = polyline.colors.first() {
+            object_data.color = *color;
+        }
+
+        let mut line = Line::new();
+        line.add_polyline(&local_polyline(polyline, &mut object_data))?;
+        line.set(0, &object_data);
+        line.load()?;
+
+        Ok(Self::from_object(Box::new(line), object_data))
+    }
+
+    // create a new polygon Shader
+    pub fn polygon(polygon: &PolygonData) -> Result<Self, String> {
+        Self::polygon_object(Polygon::new(), polygon)
+    }
+
+    // create a new polygon Shader that is filled with an image
+    pub fn textured_polygon(polygon: &PolygonData, image: &Image) -> Result<Self, String> {
+        Self::polygon_object(Polygon::textured(image), polygon)
     }
 
     // create a new texture Shader
@@ -76,13 +108,56 @@ impl Shader {
         texture.add(&object_data);
         texture.load()?;
 
-        let component = Self {
-            object: Box::new(texture),
-            object_data,
-            transform: Transform::default(),
+        Ok(Self::from_object(Box::new(texture), object_data))
+    }
+
+    // create a new texture Shader
+    // that is sampled with options
+    pub fn texture_with_options(image: &Image, options: &TextureOptions) -> Result<Self, String> {
+        // create the data that is used to create
+        // the transform buffer in the shader
+        let object_data = ObjectData {
+            dim: (image.width, image.height),
+            ..ObjectData::default()
         };
 
-        Ok(component)
+        let mut texture = Texture::with_options(image, options);
+        texture.add(&object_data);
+        texture.load()?;
+
+        Ok(Self::from_object(Box::new(texture), object_data))
+    }
+
+    // create a new nine slice Shader
+    // the borders keep their size at every dim
+    pub fn nine_slice(image: &Image, slices: &NineSliceData) -> Result<Self, String> {
+        let nine_slice = NineSlice::new(image, slices, &TextureOptions::default());
+        Self::nine_slice_object(nine_slice, (image.width, image.height))
+    }
+
+    // create a new nine slice Shader
+    // out of a tile of a tilemap
+    pub fn nine_slice_tile(tilemap: &TileMap, tile: &TileData, slices: &NineSliceData) -> Result<Self, String> {
+        let dim = tilemap.dim as f32;
+        let nine_slice = NineSlice::region(&tilemap.image(), tile.region(), slices, &TextureOptions::default());
+        Self::nine_slice_object(nine_slice, (dim, dim))
+    }
+
+    // create a new texture Shader that displays
+    // everything that was drawn into the RenderTarget,
+    // the Shader keeps the size the target has now,
+    // call set_dim after the target changed its size
+    pub fn render_target(target: &RenderTarget) -> Result<Self, String> {
+        // create the data that is used to create
+        // the transform buffer in the shader
+        let mut object_data = ObjectData::default();
+        object_data.dim = target.dim();
+
+        let mut texture = Texture::from_buffer(target.texture_buffer());
+        texture.add(&object_data);
+        texture.load()?;
+
+        Ok(Self::from_object(Box::new(texture), object_data))
     }
 
     // create a new text Shader
@@ -102,19 +177,47 @@ impl Shader {
         text.add(&object_data);
         text.load()?;
 
-        let component = Self {
-            object: Box::new(text),
-            object_data,
-            transform: Transform::default(),
-        };
-
-        Ok(component)
+        Ok(Self::from_object(Box::new(text), object_data))
     }
 
     // draw the Shader to the screen
     pub fn draw(&mut self, draw: &Draw, camera: &Transform) -> Result<(), String> {
-        self.object.draw(draw, camera, &self.transform)?;
-        Ok(())
+        // skip the Shader if it is outside of the screen
+        if self.culling {
+            let area = culling::visible_area(&draw.window, camera, &self.transform);
+            let visible = culling::overlaps(culling::instance_bounds(&self.object_data), area);
+            self.object.set_visible(Some(if visible { vec![0] } else { vec![] }));
+        }
+
+        // use the blend mode only for this Shader
+        let previous = self.blend.apply();
+        let result = self.object.draw(draw, camera, &self.transform);
+        previous.apply();
+        result
+    }
+
+    // skip drawing the Shader when it is outside of the screen,
+    // lines and polygons are always drawn
+    pub fn set_culling(&mut self, culling: bool) {
+        self.culling = culling;
+        if !culling {
+            self.object.set_visible(None);
+        }
+    }
+
+    // true if the Shader is skipped outside of the screen
+    pub fn culling(&self) -> bool {
+        self.culling
+    }
+
+    // set how the Shader is combined with what is already drawn
+    pub fn set_blend(&mut self, blend: BlendMode) {
+        self.blend = blend;
+    }
+
+    // get how the Shader is combined with what is already drawn
+    pub fn blend(&self) -> BlendMode {
+        self.blend
     }
 
     // set the width and the height of the Shader
@@ -205,13 +308,261 @@ impl Shader {
         self.object_data.texcoord
     }
 
+    // set the radius of the top left, top right,
+    // bottom right and bottom left corner of a rect Shader
+    pub fn set_corner_radius(&mut self, corner_radius: [f32; 4]) {
+        self.object_data.corner_radius = corner_radius;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the corner radius of the Shader
+    pub fn corner_radius(&self) -> [f32; 4] {
+        self.object_data.corner_radius
+    }
+
+    // set the width and color of the outline of the Shader
+    pub fn set_stroke(&mut self, width: f32, color: &Color) {
+        self.object_data.stroke_width = width;
+        self.object_data.stroke_color = *color;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the width and color of the outline of the Shader
+    pub fn stroke(&self) -> (f32, Color) {
+        (self.object_data.stroke_width, self.object_data.stroke_color)
+    }
+
+    // set the inner radius of a circle Shader
+    // as a fraction of its radius, this turns the circle into a ring
+    pub fn set_inner_radius(&mut self, inner_radius: f32) {
+        self.object_data.inner_radius = inner_radius;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the inner radius of the Shader
+    pub fn inner_radius(&self) -> f32 {
+        self.object_data.inner_radius
+    }
+
+    // set the start and end angle of a circle Shader in radians
+    pub fn set_arc(&mut self, start: f32, end: f32) {
+        self.object_data.arc = (start, end);
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the start and end angle of the Shader
+    pub fn arc(&self) -> (f32, f32) {
+        self.object_data.arc
+    }
+
+    // set the drop shadow of the Shader
+    pub fn set_shadow(&mut self, shadow: &Shadow) {
+        self.object_data.shadow = *shadow;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the drop shadow of the Shader
+    pub fn shadow(&self) -> Shadow {
+        self.object_data.shadow
+    }
+
+    // set the gradient fill of the Shader,
+    // only rects and circles draw gradients
+    pub fn set_gradient(&mut self, gradient: &Gradient) {
+        self.object_data.gradient = *gradient;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the gradient fill of the Shader
+    pub fn gradient(&self) -> Gradient {
+        self.object_data.gradient
+    }
+
+    // set the rotation of the Shader in radians,
+    // clockwise around its pivot
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.object_data.rotation = rotation;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the rotation of the Shader in radians
+    pub fn rotation(&self) -> f32 {
+        self.object_data.rotation
+    }
+
+    // set the point the Shader rotates around
+    // as a fraction of its dimension
+    pub fn set_pivot(&mut self, x: f32, y: f32) {
+        self.object_data.pivot = (x, y);
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the point the Shader rotates around
+    pub fn pivot(&self) -> (f32, f32) {
+        self.object_data.pivot
+    }
+
+    // mirror the Shader horizontally and/or vertically
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) {
+        self.object_data.flip_x = flip_x;
+        self.object_data.flip_y = flip_y;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the horizontal and vertical mirroring of the Shader
+    pub fn flip(&self) -> (bool, bool) {
+        (self.object_data.flip_x, self.object_data.flip_y)
+    }
+
+    // mix the color into a texture Shader with a mode
+    pub fn set_tint(&mut self, color: &Color, mode: TintMode) {
+        self.object_data.color = *color;
+        self.object_data.tint = mode;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+    }
+
+    // get the mode that mixes the color into a texture Shader
+    pub fn tint(&self) -> TintMode {
+        self.object_data.tint
+    }
+
+    // replace the image of a texture Shader,
+    // the dimension stays the same
+    pub fn set_image(&mut self, image: &Image) -> Result<(), String> {
+        self.object.set_image(image)
+    }
+
+    // replace a part of the image of a texture Shader,
+    // x and y are from the top left corner of the image
+    pub fn set_sub_image(&mut self, x: u32, y: u32, image: &Image) -> Result<(), String> {
+        self.object.set_sub_image(x, y, image)
+    }
+
+    // render a new text with a text Shader,
+    // the dimension changes to the size of the new text
+    pub fn set_text(&mut self, text: &str, font: &Font, font_size: usize) -> Result<(), String> {
+        let image = font.snapshot(text, font_size as f32)?;
+        let image = Image::from(image);
+        self.object.set_image(&image)?;
+
+        self.object_data.dim = (image.width, image.height);
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+        Ok(())
+    }
+
+    // change how the texture of a texture Shader is sampled
+    pub fn set_texture_options(&mut self, options: &TextureOptions) -> Result<(), String> {
+        self.object.set_texture_options(options)
+    }
+
+    // replace the polyline of a line Shader
+    // the offset and dim become the rect around the new points
+    pub fn set_polyline(&mut self, polyline: &Polyline) -> Result<(), String> {
+        let polyline = local_polyline(polyline, &mut self.object_data);
+        self.object.set_polyline(0, &polyline)?;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+        Ok(())
+    }
+
+    // replace the polygon of a polygon Shader
+    // the offset and dim become the rect around the new points
+    pub fn set_polygon(&mut self, polygon: &PolygonData) -> Result<(), String> {
+        let polygon = local_polygon(polygon, &mut self.object_data);
+        self.object.set_polygon(0, &polygon)?;
+        self.object.set(0, &self.object_data);
+        self.object.set_state(ObjectState::Reload);
+        Ok(())
+    }
+
     // implement collision on
     // both Shader types
     pub fn collides(&self, x: f32, y: f32) -> bool {
         let (tx, ty, _) = self.transform.pos();
         let (x_offset, y_offset) = self.offset();
         let (width, height) = self.dim();
-        point_in_rect(x, y, tx + x_offset, ty + y_offset, width, height)
+        let (pivot_x, pivot_y) = self.object_data.pivot_position();
+
+        // rotate the point back so the rect is straight again
+        let (rect_x, rect_y) = (tx + x_offset, ty + y_offset);
+        let (x, y) = rotate_point(x, y, rect_x + pivot_x, rect_y + pivot_y, -self.rotation());
+        point_in_rect(x, y, rect_x, rect_y, width, height)
+    }
+
+    // a Shader that draws the object with its only object data
+    fn from_object(object: Box<dyn Object>, object_data: ObjectData) -> Self {
+        Self {
+            object,
+            object_data,
+            transform: Transform::default(),
+            blend: BlendMode::default(),
+            culling: false,
+        }
+    }
+
+    // create a Shader out of a nine slice object
+    // with the size of the image
+    fn nine_slice_object(mut nine_slice: NineSlice, dim: (f32, f32)) -> Result<Self, String> {
+        let object_data = ObjectData {
+            dim,
+            ..ObjectData::default()
+        };
+
+        nine_slice.add(&object_data);
+        nine_slice.load()?;
+
+        Ok(Self::from_object(Box::new(nine_slice), object_data))
+    }
+
+    // load a polygon object with a single polygon
+    // the offset and dim are the rect around the points
+    fn polygon_object(mut object: Polygon, polygon: &PolygonData) -> Result<Self, String> {
+        // the object data of a polygon holds
+        // the color of all points
+        let mut object_data = ObjectData::default();
+        if let Some(color) = polygon.colors().first() {
+            object_data.color = *color;
+        }
+
+        object.add_polygon(&local_polygon(polygon, &mut object_data))?;
+        object.set(0, &object_data);
+        object.load()?;
+
+        Ok(Self::from_object(Box::new(object), object_data))
     }
 }
 
+// the polyline moved to the top left of the rect around its points,
+// the rect becomes the offset and dim of the object data
+fn local_polyline(polyline: &Polyline, object_data: &mut ObjectData) -> Polyline {
+    let (x, y, width, height) = polyline.bounds();
+    object_data.offset = (x, y);
+    object_data.dim = (width, height);
+
+    let mut polyline = polyline.clone();
+    polyline.translate(-x, -y);
+    polyline
+}
+
+// the polygon moved to the top left of the rect around its points,
+// the rect becomes the offset and dim of the object data
+fn local_polygon(polygon: &PolygonData, object_data: &mut ObjectData) -> PolygonData {
+    let (x, y, width, height) = polygon.bounds();
+    object_data.offset = (x, y);
+    object_data.dim = (width, height);
+
+    let mut polygon = polygon.clone();
+    polygon.translate(-x, -y);
+    polygon
+}
@@ -0,0 +1,192 @@
+use crate::core::color::Color;
+use crate::core::shader::object::{Framebuffer, Renderbuffer, TextureBuffer};
+use crate::core::window::Window;
+use crate::Draw;
+use gl::types::*;
+use std::rc::Rc;
+
+/// A texture that can be drawn into instead of the window
+///
+/// create the target in load, it will follow the size of the window
+/// ```rust
+/// let mut target = RenderTarget::window(&Window::new(1270.0, 700.0)).unwrap();
+/// let mut screen = Shader::render_target(&target).unwrap();
+/// ```
+///
+/// redirect draw calls into the target in update
+/// and draw the result like any other texture
+/// ```rust
+/// target.bind(&draw).unwrap();
+/// target.clear(&Color::grey(0));
+/// rect.draw(&draw, &camera).unwrap();
+/// target.unbind(&draw);
+/// screen.draw(&draw, &camera).unwrap();
+/// ```
+///
+/// a Shader of the target keeps the size the target had when
+/// the Shader was created, it gets stretched after a resize
+/// ```rust
+/// target.resize(640, 360)?;
+/// screen.set_dim(target.width(), target.height());
+/// ```
+///
+/// unbind goes back to whatever was drawn to before bind,
+/// so targets can be used while another target is bound
+pub struct RenderTarget {
+    framebuffer: Framebuffer,
+    texture_buffer: Rc<TextureBuffer>,
+    // the depth and stencil buffer, the stencil is used for masks and shadows
+    depth_stencil: Renderbuffer,
+    width: f32,
+    height: f32,
+    follow_window: bool,
+    // the framebuffer and viewport before bind
+    previous: Option<FramebufferState>,
+}
+
+impl RenderTarget {
+    // create a RenderTarget with a fixed width and height
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let mut target = Self {
+            framebuffer: Framebuffer::new(),
+            texture_buffer: Rc::new(TextureBuffer::new()),
+            depth_stencil: Renderbuffer::new(),
+            width: 0.0,
+            height: 0.0,
+            follow_window: false,
+            previous: None,
+        };
+
+        target.resize(width, height)?;
+        Ok(target)
+    }
+
+    // create a RenderTarget that has the size of the window
+    // and resizes itself when the window gets resized
+    pub fn window(window: &Window) -> Result<Self, String> {
+        let mut target = Self::new(window.width as u32, window.height as u32)?;
+        target.follow_window = true;
+        Ok(target)
+    }
+
+    // change the size of the texture,
+    // this will delete everything that was drawn into it
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err(format!("Cannot create a RenderTarget of size {}x{}.", width, height));
+        }
+
+        self.texture_buffer.allocate(width as i32, height as i32);
+        self.framebuffer.attach(&self.texture_buffer)?;
+        self.depth_stencil.allocate_depth_stencil(width as i32, height as i32);
+        self.framebuffer.attach_depth_stencil(&self.depth_stencil)?;
+        self.width = width as f32;
+        self.height = height as f32;
+        Ok(())
+    }
+
+    // redirect all following draw calls into this target
+    // resizes the target first if it follows the window
+    pub fn bind(&mut self, draw: &Draw) -> Result<(), String> {
+        let window = draw.window;
+        let resized = window.width != self.width || window.height != self.height;
+        if self.follow_window && resized {
+            self.resize(window.width as u32, window.height as u32)?;
+        }
+
+        self.previous = Some(FramebufferState::current());
+        self.framebuffer.bind();
+
+        unsafe {
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+
+        Ok(())
+    }
+
+    // draw to where was drawn before bind again,
+    // the window if the target was never bound
+    pub fn unbind(&self, draw: &Draw) {
+        match &self.previous {
+            Some(previous) => previous.restore(),
+            None => {
+                self.framebuffer.unbind();
+                draw.window.apply();
+            }
+        }
+    }
+
+    // fill the whole target with one color and
+    // empty its stencil buffer, the target has to be bound
+    pub fn clear(&self, color: &Color) {
+        unsafe {
+            // remember the background color of the window
+            let mut background = [0.0f32; 4];
+            gl::GetFloatv(gl::COLOR_CLEAR_VALUE, background.as_mut_ptr());
+
+            gl::ClearColor(color.r, color.g, color.b, color.a);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+            gl::ClearColor(background[0], background[1], background[2], background[3]);
+        }
+    }
+
+    // get the width and the height of the RenderTarget
+    pub fn dim(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    // get the width of the RenderTarget
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    // get the height of the RenderTarget
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    // the texture the target draws into,
+    // shared with texture objects that display it
+    pub(crate) fn texture_buffer(&self) -> Rc<TextureBuffer> {
+        self.texture_buffer.clone()
+    }
+}
+
+/*
+the framebuffer and viewport of the graphics card at one moment,
+used to go back to them after drawing into a target
+*/
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FramebufferState {
+    framebuffer: GLint,
+    viewport: [GLint; 4],
+}
+
+impl FramebufferState {
+    // read the framebuffer and viewport that are active right now
+    pub(crate) fn current() -> Self {
+        let mut state = Self::default();
+
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut state.framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, state.viewport.as_mut_ptr());
+        }
+
+        state
+    }
+
+    // the viewport in pixels of the framebuffer, y from the bottom
+    pub(crate) fn viewport(&self) -> [GLint; 4] {
+        self.viewport
+    }
+
+    // draw into the framebuffer and viewport again
+    pub(crate) fn restore(&self) {
+        let [x, y, width, height] = self.viewport;
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer as GLuint);
+            gl::Viewport(x, y, width, height);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::core::camera::Camera2D;
+use crate::core::color::Color;
+use crate::core::mouse::Mouse;
+use crate::core::project::Draw;
+use crate::core::shader::clip::{self, ScissorState};
+use crate::core::shader::target::FramebufferState;
+use crate::core::window::Window;
+
+/// a part of the window with its own camera,
+/// used to draw the world more than once in a frame
+/// ```rust
+/// // in load
+/// self.viewports = Viewport::split(&Window::new(1270.0, 700.0), 2);
+///
+/// // in update
+/// let world = &mut self.world;
+/// for (viewport, player) in self.viewports.iter_mut().zip(&self.players) {
+///     viewport.camera.follow(player.x, player.y, draw.performance.delta());
+///     viewport.draw(&draw, |draw, camera| world.draw(draw, camera))?;
+/// }
+/// ```
+pub struct Viewport {
+    // x, y, width and height in the units of the window
+    rect: (f32, f32, f32, f32),
+    // the camera of this viewport, its viewport is the size of the rect
+    pub camera: Camera2D,
+    // fill the viewport before drawing
+    clear_color: Option<Color>,
+}
+
+impl Viewport {
+    // create a viewport at x and y of width and height
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            rect: (x, y, width, height),
+            camera: Camera2D::new(width, height),
+            clear_color: None,
+        }
+    }
+
+    // split the window into count viewports for split screen,
+    // 2 are side by side, 3 and 4 are in a grid
+    pub fn split(window: &Window, count: usize) -> Vec<Self> {
+        split_rects(window.width, window.height, count)
+            .into_iter()
+            .map(|(x, y, width, height)| Self::new(x, y, width, height))
+            .collect()
+    }
+
+    // move and resize the viewport,
+    // the camera keeps looking at the same point
+    pub fn set_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.rect = (x, y, width, height);
+        self.camera.set_viewport(0.0, 0.0, width, height);
+    }
+
+    // x, y, width and height in the units of the window
+    pub fn rect(&self) -> (f32, f32, f32, f32) {
+        self.rect
+    }
+
+    // fill the viewport with a color before drawing, None draws over the window
+    pub fn set_clear_color(&mut self, color: Option<Color>) {
+        self.clear_color = color;
+    }
+
+    // true if a point of the window, e.g. the mouse, is in the viewport
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let (rect_x, rect_y, width, height) = self.rect;
+        x >= rect_x && x < rect_x + width && y >= rect_y && y < rect_y + height
+    }
+
+    /*
+    draw into the viewport, f gets a Draw where the window
+    is the size of the viewport and the mouse is relative to it,
+    everything outside of the viewport gets cut off
+    */
+    pub fn draw<F>(&self, draw: &Draw, f: F) -> Result<(), String>
+    where
+        F: FnOnce(&Draw, &Camera2D) -> Result<(), String>,
+    {
+        let (x, y, width, height) = self.rect;
+        let window = draw.window.sub(x, y, width, height);
+
+        let viewport_draw = Draw {
+            performance: draw.performance.clone(),
+            window,
+            mouse: Mouse::new(
+                draw.mouse.x - x,
+                draw.mouse.y - y,
+                draw.mouse.left(),
+                draw.mouse.right(),
+                draw.mouse.mws(),
+            ),
+            keys: draw.keys.clone(),
+        };
+
+        // the rect is a part of the bound viewport, which is
+        // not the window when a render target is bound
+        let scissor = ScissorState::current();
+        let bound = FramebufferState::current();
+        let window_size = (draw.window.width, draw.window.height);
+        let [gl_x, gl_y, gl_width, gl_height] = clip::scissor_box(bound.viewport(), window_size, self.rect);
+
+        unsafe {
+            gl::Viewport(gl_x, gl_y, gl_width, gl_height);
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(gl_x, gl_y, gl_width, gl_height);
+
+            if let Some(color) = self.clear_color {
+                let mut background = [0.0f32; 4];
+                gl::GetFloatv(gl::COLOR_CLEAR_VALUE, background.as_mut_ptr());
+                gl::ClearColor(color.r, color.g, color.b, color.a);
+                gl::Clear(gl::COLOR_BUFFER_BIT);
+                gl::ClearColor(background[0], background[1], background[2], background[3]);
+            }
+        }
+
+        let result = f(&viewport_draw, &self.camera);
+
+        scissor.restore();
+        bound.restore();
+        result
+    }
+}
+
+// the rects of count viewports that split width and height
+fn split_rects(width: f32, height: f32, count: usize) -> Vec<(f32, f32, f32, f32)> {
+    let (columns, rows) = match count {
+        0 => return vec![],
+        1 => (1, 1),
+        2 => (2, 1),
+        3 | 4 => (2, 2),
+        _ => {
+            let columns = (count as f32).sqrt().ceil() as usize;
+            (columns, (count as f32 / columns as f32).ceil() as usize)
+        }
+    };
+
+    let cell_width = width / columns as f32;
+    let cell_height = height / rows as f32;
+    (0..count)
+        .map(|i| {
+            let (column, row) = (i % columns, i / columns);
+            (column as f32 * cell_width, row as f32 * cell_height, cell_width, cell_height)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::window::Scaling;
+
+    #[test]
+    fn test_split_rects() {
+        assert!(split_rects(800.0, 600.0, 0).is_empty());
+        assert_eq!(vec![(0.0, 0.0, 400.0, 600.0), (400.0, 0.0, 400.0, 600.0)], split_rects(800.0, 600.0, 2));
+
+        let rects = split_rects(800.0, 600.0, 3);
+        assert_eq!(3, rects.len());
+        assert_eq!((0.0, 300.0, 400.0, 300.0), rects[2]);
+
+        // 5 viewports need 3 columns and 2 rows
+        let rects = split_rects(900.0, 600.0, 5);
+        assert_eq!((600.0, 0.0, 300.0, 300.0), rects[2]);
+        assert_eq!((300.0, 300.0, 300.0, 300.0), rects[4]);
+    }
+
+    #[test]
+    fn test_scaled_rect() {
+        // a letterboxed window has bars above and below
+        let window = Window::scaled(Scaling::Letterbox(400.0, 200.0), 1000.0, 600.0);
+        let (x, y, width, height) = window.gl_viewport();
+        let rect = (200.0, 0.0, 200.0, 200.0);
+        assert_eq!([500, 50, 500, 500], clip::scissor_box([x, y, width, height], (400.0, 200.0), rect));
+
+        // a render target of the virtual size has no bars
+        assert_eq!([200, 0, 200, 200], clip::scissor_box([0, 0, 400, 200], (400.0, 200.0), rect));
+    }
+
+    #[test]
+    fn test_contains() {
+        let viewport = Viewport::new(400.0, 0.0, 400.0, 600.0);
+        assert!(viewport.contains(400.0, 10.0));
+        assert!(!viewport.contains(399.0, 10.0));
+        assert!(!viewport.contains(800.0, 10.0));
+    }
+}
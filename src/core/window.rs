@@ -1,17 +1,176 @@
-// this struct holds the information 
+// this struct holds the information
 // of the actuall window (e.g width height)
 // we don't want to allow to write to width and
 // height
+//
+// width and height are the virtual resolution
+// the game draws in, the viewport is the part
+// of the real window it gets scaled into
 
 #[derive(Copy,Clone)]
 pub struct Window {
     pub width: f32,
     pub height: f32,
+    // x, y, width and height in pixels, y from the top
+    viewport: (f32, f32, f32, f32),
+    // the size of the real window in pixels
+    screen: (f32, f32),
+}
+
+/// how the virtual resolution of the game
+/// gets scaled into the real window
+/// ```rust
+/// let config = Config {
+///     scaling: Scaling::PixelPerfect(320.0, 180.0),
+///     ..Config::default()
+/// };
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Scaling {
+    // the virtual resolution is the size of the window,
+    // 1 unit is 1 pixel
+    #[default]
+    None,
+    // keep the width and height and scale them as big as possible,
+    // the rest of the window gets bars
+    Letterbox(f32, f32),
+    // like letterbox but only scale by whole numbers
+    // so every pixel has the same size
+    PixelPerfect(f32, f32),
+    // show at least width and height and fill
+    // the whole window by showing more of the world
+    Expand(f32, f32),
 }
 
 impl Window {
     // create a new window struct
     pub fn new(width: f32, height: f32) -> Window {
-        Window {width, height}
+        Window {width, height, viewport: (0.0, 0.0, width, height), screen: (width, height)}
+    }
+
+    // create the window struct for a real window
+    // of width and height with a scaling
+    pub fn scaled(scaling: Scaling, width: f32, height: f32) -> Window {
+        // the virtual size and the number of pixels per unit
+        let (virtual_width, virtual_height, scale) = match scaling {
+            Scaling::None => (width, height, 1.0),
+            Scaling::Letterbox(w, h) => (w, h, (width / w).min(height / h)),
+            Scaling::PixelPerfect(w, h) => (w, h, (width / w).min(height / h).floor().max(1.0)),
+            Scaling::Expand(w, h) => {
+                let scale = (width / w).min(height / h);
+                (width / scale, height / scale, scale)
+            }
+        };
+
+        let viewport_width = (virtual_width * scale).round();
+        let viewport_height = (virtual_height * scale).round();
+        let viewport = (
+            ((width - viewport_width) / 2.0).floor(),
+            ((height - viewport_height) / 2.0).floor(),
+            viewport_width,
+            viewport_height,
+        );
+
+        Window {width: virtual_width, height: virtual_height, viewport, screen: (width, height)}
+    }
+
+    // a part of the window in virtual units as its own window,
+    // used to draw into a viewport
+    pub fn sub(&self, x: f32, y: f32, width: f32, height: f32) -> Window {
+        let (viewport_x, viewport_y, viewport_width, viewport_height) = self.viewport;
+        let scale_x = viewport_width / self.width;
+        let scale_y = viewport_height / self.height;
+
+        let left = (viewport_x + x * scale_x).round();
+        let top = (viewport_y + y * scale_y).round();
+        let viewport = (
+            left,
+            top,
+            (viewport_x + (x + width) * scale_x).round() - left,
+            (viewport_y + (y + height) * scale_y).round() - top,
+        );
+
+        Window {width, height, viewport, screen: self.screen}
+    }
+
+    // the part of the real window that is drawn to,
+    // x, y, width and height in pixels, y from the top
+    pub fn viewport(&self) -> (f32, f32, f32, f32) {
+        self.viewport
+    }
+
+    // the size of the real window in pixels
+    pub fn screen(&self) -> (f32, f32) {
+        self.screen
+    }
+
+    // true if parts of the real window are not drawn to
+    pub fn has_bars(&self) -> bool {
+        let (x, y, _, _) = self.viewport;
+        x > 0.0 || y > 0.0
     }
-}
\ No newline at end of file
+
+    // convert a point in the real window, e.g. the mouse,
+    // into the virtual resolution
+    pub fn to_virtual(&self, x: f32, y: f32) -> (f32, f32) {
+        let (viewport_x, viewport_y, viewport_width, viewport_height) = self.viewport;
+        (
+            (x - viewport_x) * self.width / viewport_width,
+            (y - viewport_y) * self.height / viewport_height,
+        )
+    }
+
+    // draw into the viewport
+    pub(crate) fn apply(&self) {
+        let (x, y, width, height) = self.gl_viewport();
+        unsafe {
+            gl::Viewport(x, y, width, height);
+        }
+    }
+
+    // the viewport the way opengl wants it, y from the bottom
+    pub(crate) fn gl_viewport(&self) -> (i32, i32, i32, i32) {
+        let (x, y, width, height) = self.viewport;
+        let bottom = self.screen.1 - y - height;
+        (x as i32, bottom as i32, width as i32, height as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaled() {
+        let window = Window::scaled(Scaling::None, 800.0, 600.0);
+        assert_eq!((800.0, 600.0), (window.width, window.height));
+        assert!(!window.has_bars());
+
+        // bars on the left and right
+        let window = Window::scaled(Scaling::Letterbox(400.0, 200.0), 1000.0, 600.0);
+        assert_eq!((400.0, 200.0), (window.width, window.height));
+        assert_eq!((0.0, 50.0, 1000.0, 500.0), window.viewport());
+        assert_eq!((0, 50, 1000, 500), window.gl_viewport());
+        assert_eq!((200.0, 100.0), window.to_virtual(500.0, 300.0));
+
+        // 1000 / 320 = 3.125, so every pixel is 3x3
+        let window = Window::scaled(Scaling::PixelPerfect(320.0, 180.0), 1000.0, 600.0);
+        assert_eq!((20.0, 30.0, 960.0, 540.0), window.viewport());
+        assert_eq!((0.0, 0.0), window.to_virtual(20.0, 30.0));
+
+        // a wider window shows more of the world
+        let window = Window::scaled(Scaling::Expand(400.0, 300.0), 1000.0, 600.0);
+        assert_eq!((500.0, 300.0), (window.width, window.height));
+        assert_eq!((0.0, 0.0, 1000.0, 600.0), window.viewport());
+    }
+
+    #[test]
+    fn test_sub() {
+        let window = Window::scaled(Scaling::Letterbox(400.0, 200.0), 1000.0, 600.0);
+        let right = window.sub(200.0, 0.0, 200.0, 200.0);
+        assert_eq!((200.0, 200.0), (right.width, right.height));
+        assert_eq!((500.0, 50.0, 500.0, 500.0), right.viewport());
+        assert_eq!((500, 50, 500, 500), right.gl_viewport());
+        assert_eq!((0.0, 100.0), right.to_virtual(500.0, 300.0));
+    }
+}
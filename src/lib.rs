@@ -5,15 +5,34 @@ pub mod core;
 
 // reexports
 
+pub use crate::core::camera::Camera2D;
 pub use crate::core::color::Color;
 pub use crate::core::math::collision;
+pub use crate::core::math::culling::SpatialGrid;
 pub use crate::core::math::transform::Transform;
 pub use crate::core::project::{Config, Draw, Runtime};
 pub use crate::core::resource::font::Font;
 pub use crate::core::resource::image::Image;
+pub use crate::core::resource::layout::{Align, GlyphPosition, LayoutOptions, TextLayout, TextLine};
 pub use crate::core::seed::Seed;
-pub use crate::core::shader::data::ObjectData;
+pub use crate::core::shader::blend::BlendMode;
+pub use crate::core::shader::clip;
+pub use crate::core::shader::data::{Gradient, GradientKind, ObjectData, Shadow, TintMode};
+pub use crate::core::shader::glyphs::{GlyphAtlas, GlyphText};
+pub use crate::core::shader::handle::{InstanceHandle, InstanceRef};
+pub use crate::core::shader::layer::{Drawable, Layer, Layers, RenderQueue};
+pub use crate::core::shader::lighting::{Light, LightKind, Lighting};
+pub use crate::core::shader::mesh::{Attribute, Mesh, MeshBuilder, Primitive, Usage};
+pub use crate::core::shader::object::{Filter, TextureOptions, Wrap};
+pub use crate::core::shader::object::line::{LineCap, LineJoin, Polyline};
+pub use crate::core::shader::object::nineslice::{NineSliceData, SliceMode};
+pub use crate::core::shader::object::polygon::PolygonData;
+pub use crate::core::shader::particles::{Curve, Emitter, EmitterSettings, EmitterShape, ParticleSpace};
+pub use crate::core::shader::postprocess::{Pass, PostProcess};
+pub use crate::core::shader::target::RenderTarget;
 pub use crate::core::shader::{instanced::InstancedShader, shader::Shader};
+pub use crate::core::viewport::Viewport;
+pub use crate::core::window::{Scaling, Window};
 
 use crate::core::mouse;
 use crate::core::project::Performance;
@@ -42,6 +61,8 @@ pub fn init(
     gl_attr.set_context_version(3, 3);
     // double buffering
     gl_attr.set_double_buffer(true);
+    // a stencil buffer for masks
+    gl_attr.set_stencil_size(8);
 
     // create the window using opengl and make it resizable
     let mut window = video_subsystem
@@ -65,8 +86,10 @@ pub fn init(
     // set vsync
     video_subsystem.gl_set_swap_interval(1).unwrap();
 
-    // set the viewport to a the initial values
-    set_viewport(config.width as i32, config.height as i32);
+    // create the window struct with the virtual width and height
+    // and set the viewport to the initial values
+    let mut win = core::window::Window::scaled(config.scaling, config.width as f32, config.height as f32);
+    win.apply();
 
     // event_pump holds all user input events like key or mouse button clicks
     let mut event_pump = sdl.event_pump().unwrap();
@@ -80,9 +103,6 @@ pub fn init(
         gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
     }
 
-    // create the window struct with width and height
-    let mut win = core::window::Window::new(config.width as f32, config.height as f32);
-
     // create the performance object
     let mut performance = Performance::new();
 
@@ -103,8 +123,8 @@ pub fn init(
             if let sdl2::event::Event::Window { win_event, .. } = event {
                 if let sdl2::event::WindowEvent::Resized(width, height) = win_event {
                     // create the window struct with width and height
-                    win = core::window::Window::new(width as f32, height as f32);
-                    set_viewport(width, height);
+                    win = core::window::Window::scaled(config.scaling, width as f32, height as f32);
+                    win.apply();
                 }
             }
 
@@ -119,10 +139,12 @@ pub fn init(
         }
 
         // create a new mouse struct thats holds the data for our draw struct
+        // the position is mapped into the virtual resolution
         let mouse_state = event_pump.mouse_state();
+        let (mouse_x, mouse_y) = win.to_virtual(mouse_state.x() as f32, mouse_state.y() as f32);
         let mouse = mouse::Mouse::new(
-            mouse_state.x() as f32,
-            mouse_state.y() as f32,
+            mouse_x,
+            mouse_y,
             mouse_state.left(),
             mouse_state.right(),
             mws,
@@ -151,10 +173,8 @@ pub fn init(
             keys: keys,
         };
 
-        unsafe {
-            // clear the screen
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-        }
+        // clear the screen
+        clear(&win, &config);
 
         // call the projects draw method
         runtime.update(&draw);
@@ -168,11 +188,27 @@ pub fn init(
 }
 
 /*
-always set the viewport to be a square so
-rects on different resolutions are the same ratio
+clear the window with the background color,
+bars around the viewport get the letterbox color
 */
-fn set_viewport(width: i32, height: i32) {
+fn clear(window: &core::window::Window, config: &Config) {
     unsafe {
-        gl::Viewport(0, 0, width, height);
+        if !window.has_bars() {
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+            return;
+        }
+
+        let bars = config.letterbox_color;
+        let background = config.background_color;
+        gl::ClearColor(bars.r, bars.g, bars.b, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+
+        // only clear the viewport with the background color
+        let (x, y, width, height) = window.gl_viewport();
+        gl::Enable(gl::SCISSOR_TEST);
+        gl::Scissor(x, y, width, height);
+        gl::ClearColor(background.r, background.g, background.b, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+        gl::Disable(gl::SCISSOR_TEST);
     }
 }